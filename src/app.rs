@@ -1,20 +1,75 @@
+use crate::cache::ProjectCache;
+use crate::clone::{CloneField, CloneJob, CloneOptions, CloneRequest, CloneState, CloneUpdate};
+use crate::details::{self, ProjectDetails};
 use crate::error::{IdeaError, Result};
-use crate::models::{AppMode, Config, ProjectInfo};
+use crate::fuzzy::{FuzzyMatch, fuzzy_match};
+use crate::git::{self, GitBackend};
+use crate::giturl;
+use crate::ide;
+use crate::manifest::{Batch, Manifest, ManifestEntry, Outcome};
+use crate::query::Query;
+use crate::scan::Scanner;
+use crate::models::{
+    AppMode, Branch, Category, Config, DEFAULT_IDE, GitUpdate, IdeLauncher, PendingCheckout,
+    ProjectInfo, ProjectList, ProjectRoot, SortOrder, root_name_for,
+};
 use ratatui::widgets::{ListState, TableState};
-use std::{fs, path::{PathBuf, Path}, process, time::Instant};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
 
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 
+/// Number of projects listed in the Top Projects view.
+const TOP_PROJECTS: usize = 20;
+/// Search score added per frecency point, capped so a frecent project cannot outrank a
+/// much better match.
+const FRECENCY_BOOST: f64 = 4.0;
+const MAX_FRECENCY_BOOST: i64 = 32;
+/// Manifest clones running at once; the rest wait for one of them to finish.
+const MAX_MANIFEST_CLONES: usize = 8;
+
+/// Whether `path` exists or names a command on `PATH`.
+fn launcher_found(path: &str) -> bool {
+    Path::new(path).exists()
+        || process::Command::new("which")
+            .arg(path)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 pub struct App {
     pub mode: AppMode,
     pub previous_mode: Option<AppMode>,
     pub config: Config,
     pub menu_items: Vec<&'static str>,
     pub menu_state: ListState,
-    pub categories: Vec<String>,
+    pub categories: Vec<Category>,
     pub category_state: ListState,
-    pub selected_category: Option<String>,
+    pub selected_category: Option<Category>,
+    /// Set when "Open Existing Project" jumped straight into a root's project list.
+    pub skipped_categories: bool,
+    pub root_state: ListState,
     pub projects: Vec<ProjectInfo>,
     pub project_state: TableState,
     pub theme_items: Vec<&'static str>,
@@ -24,28 +79,67 @@ pub struct App {
     pub search_query: String,
     pub is_searching: bool,
     pub pending_project: Option<ProjectInfo>,
-    pub branches: Vec<String>,
+    pub branches: Vec<Branch>,
     pub branch_state: ListState,
+    pub pending_checkout: Option<PendingCheckout>,
+    pub clone_options: CloneOptions,
+    pub clone_field_state: ListState,
+    /// Running and finished clones, shown on the clone progress screen.
+    pub clones: Vec<CloneJob>,
+    pub clone_state: ListState,
+    next_clone_id: usize,
+    clone_tx: Sender<CloneUpdate>,
+    clone_rx: Receiver<CloneUpdate>,
+    /// The latest manifest clone, kept after it finishes so its results can be read.
+    pub manifest_batch: Option<Batch>,
+    /// Shown in a popup over the current screen until any key is pressed.
+    pub error: Option<IdeaError>,
+    /// `q` was pressed while clones were running; asks before cancelling them.
+    pub confirm_quit: bool,
+    pub git_tx: Sender<GitUpdate>,
+    pub git_rx: Receiver<GitUpdate>,
+    /// Bumped on every reload. Shared with the status workers so those of an older load
+    /// stop early instead of finishing a list that no longer exists.
+    pub git_generation: Arc<AtomicU64>,
+    pub git_backend: Arc<dyn GitBackend>,
+    /// Detail panel contents for the highlighted project, once collected.
+    pub details: Option<ProjectDetails>,
+    details_requested: Option<PathBuf>,
+    details_tx: Sender<ProjectDetails>,
+    details_rx: Receiver<ProjectDetails>,
+    pub cache: ProjectCache,
+    pub ide_items: Vec<IdeLauncher>,
+    pub ide_state: ListState,
+    pub pending_ide: Option<IdeLauncher>,
+    pub ide_checked: Vec<bool>,
 }
 
 impl App {
-    pub fn new(config: Config) -> App {
+    pub fn new(mut config: Config) -> App {
+        config.migrate();
         let mut menu_state = ListState::default();
         menu_state.select(Some(0));
         let mut project_state = TableState::default();
         project_state.select(Some(0));
         let mut theme_state = ListState::default();
         theme_state.select(Some(0));
+        let (git_tx, git_rx) = mpsc::channel();
+        let (details_tx, details_rx) = mpsc::channel();
+        let (clone_tx, clone_rx) = mpsc::channel();
 
-        let base_path = PathBuf::from(&config.base_dir);
-        let (initial_mode, initial_status) = if !base_path.exists() {
+        let mut root_state = ListState::default();
+        root_state.select(if config.roots.is_empty() { None } else { Some(0) });
+
+        let (initial_mode, initial_status) = if !config
+            .roots
+            .iter()
+            .any(|r| Path::new(&r.path).exists())
+        {
             (
-                AppMode::ChangeBaseDir,
+                AppMode::ManageRoots,
                 Some((
-                    format!(
-                        "Welcome! Your base_dir '{}' was not found. Please set a valid path.",
-                        config.base_dir
-                    ),
+                    "Welcome! None of your project roots were found. Press a to add one."
+                        .to_string(),
                     Instant::now(),
                 )),
             )
@@ -53,23 +147,28 @@ impl App {
             (AppMode::MainMenu, None)
         };
 
-        let mut app = App {
+        App {
             mode: initial_mode,
             previous_mode: None,
             config: config.clone(),
             menu_items: vec![
                 "Favorites",
                 "Recent Projects",
+                "Top Projects",
                 "Open Existing Project",
                 "Clone Repository",
+                "Clone from Manifest",
                 "Open IntelliJ IDEA",
                 "Choose Theme",
-                "Change Base Directory",
+                "Manage Project Roots",
+                "Detect Installed IDEs",
             ],
             menu_state,
             categories: Vec::new(),
             category_state: ListState::default(),
             selected_category: None,
+            skipped_categories: false,
+            root_state,
             projects: Vec::new(),
             project_state,
             theme_items: vec![
@@ -86,32 +185,93 @@ impl App {
                 "Ayu Mirage",
             ],
             theme_state,
-            input: config.base_dir.clone(),
+            input: String::new(),
             status_message: initial_status,
             search_query: String::new(),
             is_searching: false,
             pending_project: None,
             branches: Vec::new(),
             branch_state: ListState::default(),
-        };
+            pending_checkout: None,
+            clone_options: CloneOptions::default(),
+            clone_field_state: ListState::default(),
+            clones: Vec::new(),
+            clone_state: ListState::default(),
+            next_clone_id: 0,
+            clone_tx,
+            clone_rx,
+            manifest_batch: None,
+            error: None,
+            confirm_quit: false,
+            git_tx,
+            git_rx,
+            git_generation: Arc::new(AtomicU64::new(0)),
+            git_backend: git::backend(),
+            details: None,
+            details_requested: None,
+            details_tx,
+            details_rx,
+            cache: ProjectCache::load(),
+            ide_items: Vec::new(),
+            ide_state: ListState::default(),
+            pending_ide: None,
+            ide_checked: Vec::new(),
+        }
+    }
 
-        // Still check for IDEA path, but don't block setup for it.
-        let idea_path = PathBuf::from(&app.config.idea_path);
-        if !idea_path.exists() {
-            let in_path = process::Command::new("which")
-                .arg(&app.config.idea_path)
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-            if !in_path {
-                app.status_message = Some((
-                    format!("Warning: idea_path '{}' not found!", app.config.idea_path),
-                    Instant::now(),
-                ));
-            }
+    pub fn idea_path_found(&self) -> bool {
+        launcher_found(&self.config.idea_path)
+    }
+
+    /// Whether `idea_path` or any of the configured `ides` can be started.
+    pub fn any_ide_found(&self) -> bool {
+        self.idea_path_found() || self.config.ides.iter().any(|i| launcher_found(&i.path))
+    }
+
+    /// The IDE setup opens by itself only while no IDE can be started and it hasn't been
+    /// skipped before.
+    pub fn needs_ide_setup(&self) -> bool {
+        !self.config.ide_setup_dismissed && !self.any_ide_found()
+    }
+
+    pub fn start_ide_setup(&mut self) {
+        self.ide_items = ide::discover_ides();
+        self.ide_checked = vec![true; self.ide_items.len()];
+        self.ide_state.select(if self.ide_items.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.mode = AppMode::IdeSetup;
+    }
+
+    pub fn toggle_ide_checked(&mut self) {
+        if let Some(i) = self.ide_state.selected()
+            && let Some(checked) = self.ide_checked.get_mut(i)
+        {
+            *checked = !*checked;
         }
+    }
 
-        app
+    /// Stores the checked IDEs from the setup screen. A checked IntelliJ IDEA replaces
+    /// `idea_path`; everything else is added to `ides`, replacing entries of the same name.
+    pub fn save_ide_setup(&mut self) {
+        let mut added = 0;
+        for (ide, checked) in self.ide_items.iter().zip(&self.ide_checked) {
+            if !checked {
+                continue;
+            }
+            if ide.name == DEFAULT_IDE {
+                self.config.idea_path = ide.path.clone();
+            } else {
+                self.config.ides.retain(|i| i.name != ide.name);
+                self.config.ides.push(ide.clone());
+            }
+            added += 1;
+        }
+        let _ = self.save_config();
+        self.status_message = Some((format!("Saved {} IDE(s)!", added), Instant::now()));
+        self.mode = AppMode::MainMenu;
     }
 
     pub fn save_config(&self) -> Result<()> {
@@ -119,7 +279,11 @@ impl App {
         Ok(())
     }
 
+    /// Moves `path` to the front of the recent list and counts the open for frecency.
     pub fn add_to_recent(&mut self, path: String) {
+        let stats = self.config.history.entry(path.clone()).or_default();
+        stats.count += 1;
+        stats.last_opened = now_secs();
         self.config.recent_projects.retain(|x| x != &path);
         self.config.recent_projects.insert(0, path);
         self.config.recent_projects.truncate(10);
@@ -127,6 +291,10 @@ impl App {
     }
 
     pub fn refresh_current_view(&mut self) {
+        self.details_requested = None;
+        for p in &self.projects {
+            self.cache.invalidate(&p.path);
+        }
         self.reload_current_view();
         self.status_message = Some(("Status refreshed!".to_string(), Instant::now()));
     }
@@ -143,6 +311,7 @@ impl App {
             }
             AppMode::Favorites => self.load_favorites(),
             AppMode::Recent => self.load_recent(),
+            AppMode::TopProjects => self.load_top_projects(),
             _ => {
                 // If in a popup mode, reload data based on where we came from
                 if let Some(prev) = &self.previous_mode {
@@ -154,28 +323,21 @@ impl App {
                         }
                         AppMode::Favorites => self.load_favorites(),
                         AppMode::Recent => self.load_recent(),
+                        AppMode::TopProjects => self.load_top_projects(),
                         _ => {}
                     }
                 }
             }
         }
         // Restore selection after reload if it exists
-        if let Some(idx) = current_selection {
-            if idx < self.projects.len() {
-                self.project_state.select(Some(idx));
-            }
+        if let Some(idx) = current_selection && idx < self.projects.len() {
+            self.project_state.select(Some(idx));
         }
     }
 
     pub fn open_terminal(&mut self) -> Result<()> {
-        let query = self.search_query.to_lowercase();
-        let filtered: Vec<&ProjectInfo> = self
-            .projects
-            .iter()
-            .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-            .collect();
-        if let Some(i) = self.project_state.selected() && i < filtered.len() {
-            let path = filtered[i].path.to_str().unwrap_or("");
+        if let Some(proj) = self.selected_project() {
+            let path = proj.path.to_str().unwrap_or("");
             let cmd_parts: Vec<&str> =
                 self.config.terminal_command.split_whitespace().collect();
             if !cmd_parts.is_empty() {
@@ -186,9 +348,9 @@ impl App {
                 command
                     .arg(path)
                     .spawn()
-                    .map_err(|e| IdeaError::Spawn(e.to_string()))?;
+                    .map_err(|e| IdeaError::Spawn(format!("{}: {}", cmd_parts[0], e)))?;
                 self.status_message = Some((
-                    format!("Opened terminal for {}!", filtered[i].name),
+                    format!("Opened terminal for {}!", proj.name),
                     Instant::now(),
                 ));
             }
@@ -197,34 +359,40 @@ impl App {
     }
 
     pub fn toggle_favorite(&mut self) {
-        let query = self.search_query.to_lowercase();
-        let filtered: Vec<&ProjectInfo> = self
-            .projects
-            .iter()
-            .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-            .collect();
-        if let Some(i) = self.project_state.selected() && i < filtered.len() {
-            let path_str = filtered[i].path.to_str().unwrap_or("").to_string();
-            if self.config.favorites.contains(&path_str) {
-                self.config.favorites.retain(|x| x != &path_str);
-                self.status_message = Some((
-                    format!("Removed {} from favorites", filtered[i].name),
-                    Instant::now(),
-                ));
-            } else {
+        if let Some(proj) = self.selected_project() {
+            let is_fav = self.is_favorite(&proj.path);
+            self.set_favorite(&proj.path, !is_fav);
+        }
+    }
+
+    pub fn is_favorite(&self, path: &Path) -> bool {
+        let path_str = path.to_str().unwrap_or("");
+        self.config.favorites.iter().any(|f| f == path_str)
+    }
+
+    pub fn set_favorite(&mut self, path: &Path, favorite: bool) {
+        let path_str = path.to_str().unwrap_or("").to_string();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        if favorite {
+            if !self.config.favorites.contains(&path_str) {
                 self.config.favorites.push(path_str);
-                self.status_message = Some((
-                    format!("Added {} to favorites", filtered[i].name),
-                    Instant::now(),
-                ));
             }
-            let _ = self.save_config();
+            self.status_message = Some((format!("Added {} to favorites", name), Instant::now()));
+        } else {
+            self.config.favorites.retain(|x| x != &path_str);
+            self.status_message =
+                Some((format!("Removed {} from favorites", name), Instant::now()));
         }
+        let _ = self.save_config();
     }
 
     pub fn load_favorites(&mut self) {
         let mut favs = Vec::new();
-        for path_str in &self.config.favorites {
+        for path_str in self.config.favorites.clone() {
             let path = PathBuf::from(path_str);
             if path.exists() {
                 let name = path
@@ -232,15 +400,7 @@ impl App {
                     .and_then(|n| n.to_str())
                     .unwrap_or("Unknown")
                     .to_string();
-                let (branch, changes) = Self::get_git_info(&path);
-                let language = Self::detect_language(&path);
-                favs.push(ProjectInfo {
-                    name,
-                    path,
-                    git_branch: branch,
-                    has_changes: changes,
-                    language,
-                });
+                favs.push(self.project_info(name, path));
             }
         }
         favs.sort_by_key(|a| a.name.to_lowercase());
@@ -251,27 +411,43 @@ impl App {
             Some(0)
         });
         self.selected_category = None;
+        self.collect_git_status();
     }
 
+    /// Projects opened through idea-tui first, followed by the ones the JetBrains IDEs
+    /// remember on their own (newest first) when `import_ide_history` is enabled.
     pub fn load_recent(&mut self) {
+        let ide_recent = if self.config.import_ide_history {
+            ide::jetbrains_recent_projects()
+        } else {
+            Vec::new()
+        };
+        let mut paths: Vec<PathBuf> = self
+            .config
+            .recent_projects
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        for r in &ide_recent {
+            if !paths.contains(&r.path) {
+                paths.push(r.path.clone());
+            }
+        }
+
         let mut recent = Vec::new();
-        for path_str in &self.config.recent_projects {
-            let path = PathBuf::from(path_str);
+        for path in paths {
             if path.exists() {
                 let name = path
                     .file_name()
                     .and_then(|n| n.to_str())
                     .unwrap_or("Unknown")
                     .to_string();
-                let (branch, changes) = Self::get_git_info(&path);
-                let language = Self::detect_language(&path);
-                recent.push(ProjectInfo {
-                    name,
-                    path,
-                    git_branch: branch,
-                    has_changes: changes,
-                    language,
-                });
+                let mut info = self.project_info(name, path);
+                if let Some(r) = ide_recent.iter().find(|r| r.path == info.path) {
+                    info.frame_title = r.frame_title.clone();
+                    info.last_opened = r.timestamp;
+                }
+                recent.push(info);
             }
         }
         self.projects = recent;
@@ -281,22 +457,83 @@ impl App {
             Some(0)
         });
         self.selected_category = None;
+        self.collect_git_status();
     }
 
+    /// The most frecent projects from `history` that still exist.
+    pub fn load_top_projects(&mut self) {
+        let now = now_secs();
+        let mut ranked: Vec<(f64, PathBuf, u64)> = self
+            .config
+            .history
+            .iter()
+            .map(|(path, stats)| (stats.frecency(now), PathBuf::from(path), stats.last_opened))
+            .filter(|(_, path, _)| path.exists())
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.truncate(TOP_PROJECTS);
+
+        let mut top = Vec::new();
+        for (_, path, last_opened) in ranked {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("Unknown")
+                .to_string();
+            let mut info = self.project_info(name, path);
+            info.last_opened = Some(last_opened);
+            top.push(info);
+        }
+        self.projects = top;
+        self.project_state.select(if self.projects.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.selected_category = None;
+        self.collect_git_status();
+    }
+
+    pub fn frecency(&self, path: &Path) -> f64 {
+        self.config
+            .history
+            .get(path.to_string_lossy().as_ref())
+            .map(|s| s.frecency(now_secs()))
+            .unwrap_or(0.0)
+    }
+
+    /// Lists every existing root as a top-level entry, followed by its visible folders.
     pub fn load_categories(&mut self) {
         let mut cats = Vec::new();
-        if let Ok(entries) = fs::read_dir(&self.config.base_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir()
-                    && let Some(name) = path.file_name().and_then(|n| n.to_str())
-                    && !name.starts_with('.')
-                {
-                    cats.push(name.to_string());
+        for root in &self.config.roots {
+            let root_path = PathBuf::from(&root.path);
+            if !root_path.is_dir() {
+                continue;
+            }
+            cats.push(Category {
+                root: root.name.clone(),
+                name: ".".to_string(),
+                path: root_path.clone(),
+            });
+            let mut folders = Vec::new();
+            if let Ok(entries) = fs::read_dir(&root_path) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.is_dir()
+                        && let Some(name) = path.file_name().and_then(|n| n.to_str())
+                        && !name.starts_with('.')
+                    {
+                        folders.push(Category {
+                            root: root.name.clone(),
+                            name: name.to_string(),
+                            path,
+                        });
+                    }
                 }
             }
+            folders.sort_by_key(|c| c.name.to_lowercase());
+            cats.extend(folders);
         }
-        cats.sort_by_key(|a| a.to_lowercase());
         self.categories = cats;
         self.category_state.select(if self.categories.is_empty() {
             None
@@ -305,27 +542,13 @@ impl App {
         });
     }
 
-    pub fn get_git_info(path: &Path) -> (Option<String>, bool) {
-        if !path.join(".git").exists() {
-            return (None, false);
-        }
-        let branch = process::Command::new("git")
-            .arg("branch")
-            .arg("--show-current")
-            .current_dir(path)
-            .output()
-            .ok()
-            .and_then(|out| String::from_utf8(out.stdout).ok())
-            .map(|s| s.trim().to_string());
-        let status = process::Command::new("git")
-            .arg("status")
-            .arg("--porcelain")
-            .current_dir(path)
-            .output()
-            .ok()
-            .map(|out| !out.stdout.is_empty())
-            .unwrap_or(false);
-        (branch, status)
+    /// Resolves `root/category`, a bare category name, or a root name.
+    pub fn find_category(&self, name: &str) -> Option<Category> {
+        self.categories
+            .iter()
+            .find(|c| c.label() == name)
+            .or_else(|| self.categories.iter().find(|c| c.name == name))
+            .cloned()
     }
 
     pub fn detect_language(path: &Path) -> Option<String> {
@@ -351,124 +574,593 @@ impl App {
         path.join(".git").exists() || Self::detect_language(path).is_some()
     }
 
-    pub fn load_projects(&mut self, category: String) {
-        let mut projs = Vec::new();
-        let cat_path = if category == "." {
-            PathBuf::from(&self.config.base_dir)
-        } else {
-            PathBuf::from(&self.config.base_dir).join(&category)
+    /// Builds a `ProjectInfo` from the cache, or without git data when the cache entry is
+    /// missing or stale. Git projects are always marked `git_loading` so
+    /// `collect_git_status` reads them again: editing a tracked file changes nothing the
+    /// cache stamps, so a cached clean status may be out of date.
+    fn project_info(&mut self, name: String, path: PathBuf) -> ProjectInfo {
+        let modified = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        if let Some(entry) = self.cache.get(&path) {
+            return ProjectInfo {
+                name,
+                git_branch: entry.git_branch.clone(),
+                has_changes: entry.has_changes,
+                language: entry.language.clone(),
+                git_loading: path.join(".git").exists(),
+                path,
+                last_commit: entry.last_commit,
+                git: entry.git.clone(),
+                modified,
+                ..ProjectInfo::default()
+            };
+        }
+        let info = ProjectInfo {
+            name,
+            language: Self::detect_language(&path),
+            git_loading: path.join(".git").exists(),
+            path,
+            modified,
+            ..ProjectInfo::default()
         };
+        if !info.git_loading {
+            self.cache.insert(&info);
+        }
+        info
+    }
+
+    fn collect_projects(&mut self, category: &Category) -> Vec<ProjectInfo> {
+        let mut projs = Vec::new();
+        let cat_path = category.path.clone();
 
         // If the category folder itself is a project, include it
-        if category != "." && Self::is_project(&cat_path) {
-            let (branch, changes) = Self::get_git_info(&cat_path);
-            let language = Self::detect_language(&cat_path);
-            projs.push(ProjectInfo {
-                name: format!(". ({})", category),
-                path: cat_path.clone(),
-                git_branch: branch,
-                has_changes: changes,
-                language,
-            });
+        if !category.is_root() && Self::is_project(&cat_path) {
+            projs.push(self.project_info(format!(". ({})", category.name), cat_path.clone()));
         }
 
-        if let Ok(entries) = fs::read_dir(&cat_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir()
-                    && let Some(name) = path.file_name().and_then(|n| n.to_str())
-                    && !name.starts_with('.')
-                {
-                    // Only add if it's actually a project or if we're in a category
-                    if Self::is_project(&path) {
-                        let (branch, changes) = Self::get_git_info(&path);
-                        let language = Self::detect_language(&path);
-                        projs.push(ProjectInfo {
-                            name: name.to_string(),
-                            path,
-                            git_branch: branch,
-                            has_changes: changes,
-                            language,
-                        });
-                    }
-                }
+        // Depth counts from the root; categories sit one level below it but always list
+        // at least their direct children.
+        let mut scanner = Scanner::from_config(&self.config);
+        if !category.is_root() {
+            scanner.max_depth = scanner.max_depth.saturating_sub(1).max(1);
+        }
+        for path in scanner.find_projects(&cat_path) {
+            let name = match self.config.project_list {
+                ProjectList::Flat => path.file_name().and_then(|n| n.to_str()).map(String::from),
+                ProjectList::Grouped => path
+                    .strip_prefix(&cat_path)
+                    .ok()
+                    .and_then(|p| p.to_str())
+                    .map(String::from),
             }
+            .unwrap_or_else(|| "Unknown".to_string());
+            projs.push(self.project_info(name, path));
         }
         projs.sort_by_key(|a| a.name.to_lowercase());
-        self.projects = projs;
+        projs
+    }
+
+    pub fn load_projects(&mut self, category: Category) {
+        self.projects = self.collect_projects(&category);
         self.project_state.select(if self.projects.is_empty() {
             None
         } else {
             Some(0)
         });
         self.selected_category = Some(category);
+        self.collect_git_status();
     }
 
-    pub fn load_branches(&mut self, path: &Path) {
-        let output = process::Command::new("git")
-            .arg("branch")
-            .arg("--format=%(refname:short)")
-            .current_dir(path)
-            .output();
-
-        if let Ok(out) = output {
-            let branches: Vec<String> = String::from_utf8_lossy(&out.stdout)
-                .lines()
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-            self.branches = branches;
-            self.branch_state.select(if self.branches.is_empty() {
-                None
-            } else {
-                Some(0)
+    /// Loads every project under all roots.
+    pub fn load_all_projects(&mut self) {
+        self.load_categories();
+        let mut all = Vec::new();
+        for cat in self.categories.clone() {
+            if cat.is_root() {
+                all.extend(self.collect_projects(&cat));
+            }
+        }
+        self.projects = all;
+        self.project_state.select(if self.projects.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.selected_category = None;
+        self.collect_git_status();
+    }
+
+    /// Starts worker threads that resolve git status for every loaded project still marked
+    /// `git_loading`. Results arrive through `git_rx` and are applied by `poll_git_updates`.
+    pub fn collect_git_status(&mut self) {
+        let generation = self.git_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut queue: Vec<PathBuf> = self
+            .projects
+            .iter()
+            .filter(|p| p.git_loading)
+            .map(|p| p.path.clone())
+            .collect();
+        if queue.is_empty() {
+            self.flush_cache();
+            return;
+        }
+        // Workers pop from the back, so reverse to fill in the visible top rows first.
+        queue.reverse();
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .min(8)
+            .min(queue.len());
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..workers {
+            let queue = Arc::clone(&queue);
+            let backend = Arc::clone(&self.git_backend);
+            let tx = self.git_tx.clone();
+            let current = Arc::clone(&self.git_generation);
+            thread::spawn(move || {
+                while current.load(Ordering::SeqCst) == generation {
+                    let next = queue.lock().ok().and_then(|mut q| q.pop());
+                    let Some(path) = next else { break };
+                    let update = GitUpdate {
+                        generation,
+                        status: backend.status(&path),
+                        last_commit: backend.last_commit(&path).unwrap_or(None),
+                        path,
+                    };
+                    if tx.send(update).is_err() {
+                        break;
+                    }
+                }
             });
         }
     }
 
-    pub fn switch_branch(&mut self, branch: &str, path: &Path) -> Result<()> {
-        let status = process::Command::new("git")
-            .arg("checkout")
-            .arg(branch)
-            .current_dir(path)
-            .stdout(process::Stdio::null())
-            .stderr(process::Stdio::null())
-            .status()
-            .map_err(|e| IdeaError::Git(e.to_string()))?;
+    pub fn poll_git_updates(&mut self) {
+        while let Ok(update) = self.git_rx.try_recv() {
+            self.apply_git_update(update);
+        }
+        self.flush_cache();
+    }
+
+    /// Blocks until every pending git status has arrived, for callers that need complete data.
+    pub fn wait_for_git_status(&mut self) {
+        while self.projects.iter().any(|p| p.git_loading) {
+            match self.git_rx.recv_timeout(Duration::from_secs(10)) {
+                Ok(update) => self.apply_git_update(update),
+                Err(_) => break,
+            }
+        }
+        self.flush_cache();
+    }
+
+    /// Writes the cache once every pending git status has been collected.
+    fn flush_cache(&mut self) {
+        if self.cache.dirty && !self.projects.iter().any(|p| p.git_loading) {
+            let _ = self.cache.save();
+        }
+    }
+
+    fn apply_git_update(&mut self, update: GitUpdate) {
+        // Results from a previous load belong to a list that no longer exists.
+        if update.generation != self.git_generation.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut updated = None;
+        for p in self.projects.iter_mut().filter(|p| p.path == update.path) {
+            p.git_loading = false;
+            match &update.status {
+                Ok(status) => {
+                    p.git_branch = status.head.clone();
+                    p.has_changes = status.is_dirty();
+                    p.git = status.clone();
+                    p.last_commit = update.last_commit;
+                    updated = Some(p.clone());
+                }
+                Err(e) => p.git_error = Some(e.to_string()),
+            }
+        }
+        // Failures are not cached so the next load tries again.
+        if let Some(info) = updated {
+            self.cache.insert(&info);
+        }
+    }
+
+    pub fn is_project_view(&self) -> bool {
+        matches!(
+            self.mode,
+            AppMode::ProjectSelection
+                | AppMode::Favorites
+                | AppMode::Recent
+                | AppMode::TopProjects
+        )
+    }
+
+    pub fn toggle_details(&mut self) {
+        self.config.show_details = !self.config.show_details;
+        let _ = self.save_config();
+    }
+
+    /// Keeps the detail panel in step with the highlighted project. Details are collected
+    /// on a background thread so moving through the list never waits on git; results for
+    /// a project that is no longer highlighted are dropped.
+    pub fn poll_details(&mut self) {
+        while let Ok(details) = self.details_rx.try_recv() {
+            if self.details_requested.as_ref() == Some(&details.path) {
+                self.details = Some(details);
+            }
+        }
+        if !self.config.show_details || !self.is_project_view() {
+            return;
+        }
+        let Some(path) = self.selected_project().map(|p| p.path) else {
+            self.details = None;
+            self.details_requested = None;
+            return;
+        };
+        if self.details_requested.as_ref() == Some(&path) {
+            return;
+        }
+        self.details_requested = Some(path.clone());
+        let tx = self.details_tx.clone();
+        let ignore = Scanner::from_config(&self.config).ignore;
+        let backend = Arc::clone(&self.git_backend);
+        thread::spawn(move || {
+            let _ = tx.send(details::collect(&path, &ignore, backend.as_ref()));
+        });
+    }
+
+    /// Matches the listed name exactly, then case-insensitively, then by folder name.
+    pub fn find_project(&self, name: &str) -> Option<ProjectInfo> {
+        let lower = name.to_lowercase();
+        self.projects
+            .iter()
+            .find(|p| p.name == name)
+            .or_else(|| self.projects.iter().find(|p| p.name.to_lowercase() == lower))
+            .or_else(|| {
+                self.projects.iter().find(|p| {
+                    p.path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.to_lowercase() == lower)
+                })
+            })
+            .cloned()
+    }
+
+    /// The `idea_path` launcher followed by every IDE configured in `ides`.
+    pub fn available_ides(&self) -> Vec<IdeLauncher> {
+        let mut ides = Vec::new();
+        if !self.config.ides.iter().any(|i| i.name == DEFAULT_IDE) {
+            ides.push(IdeLauncher {
+                name: DEFAULT_IDE.to_string(),
+                path: self.config.idea_path.clone(),
+            });
+        }
+        for ide in &self.config.ides {
+            if !ides.iter().any(|i: &IdeLauncher| i.name == ide.name) {
+                ides.push(ide.clone());
+            }
+        }
+        ides
+    }
+
+    /// Picks the IDE for a project: per-project override, then the language mapping,
+    /// then the first available launcher.
+    pub fn ide_for(&self, path: &Path, language: Option<&str>) -> IdeLauncher {
+        let ides = self.available_ides();
+        let preferred = self
+            .config
+            .project_ides
+            .get(path.to_str().unwrap_or(""))
+            .or_else(|| language.and_then(|l| self.config.language_ides.get(l)));
+        preferred
+            .and_then(|name| ides.iter().find(|i| &i.name == name))
+            .unwrap_or(&ides[0])
+            .clone()
+    }
+
+    pub fn find_ide(&self, name: &str) -> Option<IdeLauncher> {
+        self.available_ides()
+            .into_iter()
+            .find(|i| i.name.to_lowercase() == name.to_lowercase())
+    }
+
+    pub fn open_with(&mut self) {
+        if let Some(proj) = &self.pending_project {
+            let current = self.ide_for(&proj.path, proj.language.as_deref());
+            self.ide_items = self.available_ides();
+            let idx = self.ide_items.iter().position(|i| *i == current);
+            self.ide_state.select(idx.or(Some(0)));
+            self.mode = AppMode::OpenWith;
+        }
+    }
+
+    pub fn load_branches(&mut self, path: &Path) -> Result<()> {
+        self.branches = self.git_backend.branches(path)?;
+        self.branch_state.select(if self.branches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        Ok(())
+    }
+
+    /// Checks out `branch`. A remote-tracking branch is checked out through the local
+    /// branch of the same name, which is created to track it when it does not exist yet.
+    pub fn switch_branch(&mut self, branch: &Branch, path: &Path) -> Result<()> {
+        let local = branch.local_name().to_string();
+        if !branch.is_remote() {
+            self.git_backend.checkout(path, &branch.name)?;
+        } else if self.branches.iter().any(|b| !b.is_remote() && b.name == local) {
+            self.git_backend.checkout(path, &local)?;
+        } else {
+            self.git_backend.track(path, &branch.name, &local)?;
+        }
+        self.status_message = Some((
+            format!("Switched to branch {}!", local),
+            Instant::now(),
+        ));
+        self.reload_current_view();
+        Ok(())
+    }
 
-        if status.success() {
+    /// Creates the branch typed into `input` from the current `HEAD` of the pending
+    /// project, checks it out and moves on to the open confirmation.
+    pub fn create_branch(&mut self) -> Result<()> {
+        let name = self.input.trim().to_string();
+        if name.is_empty() {
+            return Ok(());
+        }
+        if !git::is_valid_branch_name(&name) {
+            self.status_message = Some((
+                format!("Invalid branch name: {}", name),
+                Instant::now(),
+            ));
+            return Ok(());
+        }
+        if self.branches.iter().any(|b| !b.is_remote() && b.name == name) {
+            self.status_message = Some((
+                format!("Branch {} already exists", name),
+                Instant::now(),
+            ));
+            return Ok(());
+        }
+        if let Some(proj) = self.pending_project.clone() {
+            self.git_backend.create_branch(&proj.path, &name)?;
             self.status_message = Some((
-                format!("Switched to branch {}!", branch),
+                format!("Created branch {}!", name),
                 Instant::now(),
             ));
             self.reload_current_view();
-            Ok(())
+            self.input.clear();
+            self.mode = AppMode::ConfirmOpen;
+        }
+        Ok(())
+    }
+
+    /// Checks out the highlighted branch, then continues to the open confirmation when
+    /// `open` is set. If the working tree has uncommitted changes the user is asked first.
+    pub fn start_checkout(&mut self, open: bool) {
+        let Some(branch) = self
+            .branch_state
+            .selected()
+            .and_then(|i| self.branches.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(path) = self.pending_project.as_ref().map(|p| p.path.clone()) else {
+            return;
+        };
+        let checkout = PendingCheckout { branch, open };
+        if self.git_backend.status(&path).is_ok_and(|s| s.is_dirty()) {
+            self.pending_checkout = Some(checkout);
+            self.mode = AppMode::ConfirmCheckout;
         } else {
-            Err(IdeaError::Git(format!("Failed to switch to branch {}", branch)))
+            self.finish_checkout(checkout, false);
+        }
+    }
+
+    /// Answers the uncommitted changes prompt: stash the changes first, or switch anyway
+    /// and let git carry them over, which it refuses if they would be overwritten.
+    pub fn confirm_checkout(&mut self, stash: bool) {
+        if let Some(checkout) = self.pending_checkout.take() {
+            self.mode = AppMode::BranchSelection;
+            self.finish_checkout(checkout, stash);
         }
     }
 
-    pub fn checkout_only(&mut self) -> Result<()> {
-        if let Some(i) = self.branch_state.selected() && i < self.branches.len() {
-            let branch = self.branches[i].clone();
-            let path = self.pending_project.as_ref().map(|p| p.path.clone());
-            if let Some(p) = path {
-                self.switch_branch(&branch, &p)?;
-                self.go_back();
+    fn finish_checkout(&mut self, checkout: PendingCheckout, stash: bool) {
+        let Some(path) = self.pending_project.as_ref().map(|p| p.path.clone()) else {
+            return;
+        };
+        if stash {
+            let message = format!("idea-tui: switching to {}", checkout.branch.local_name());
+            if let Err(e) = self.git_backend.stash(&path, &message) {
+                self.error = Some(e);
+                return;
             }
         }
-        Ok(())
+        match self.switch_branch(&checkout.branch, &path) {
+            Ok(()) if checkout.open => self.mode = AppMode::ConfirmOpen,
+            Ok(()) => self.go_back(),
+            Err(IdeaError::Git(message)) if stash => {
+                self.error = Some(IdeaError::Git(format!(
+                    "{}\n\nYour changes were stashed; restore them with `git stash pop`.",
+                    message
+                )));
+            }
+            Err(e) => self.error = Some(e),
+        }
     }
 
-    pub fn get_filtered_categories(&self) -> Vec<String> {
-        if self.search_query.is_empty() {
-            self.categories.clone()
+    /// Categories fuzzy-matching the search query on their label, best match first.
+    pub fn get_filtered_categories(&self) -> Vec<Category> {
+        // Project filter tokens do not apply to categories.
+        let query = Query::parse(&self.search_query).text;
+        if query.is_empty() {
+            return self.categories.clone();
+        }
+        let mut matches: Vec<(i64, &Category)> = self
+            .categories
+            .iter()
+            .filter_map(|c| fuzzy_match(&query, &c.label()).map(|m| (m.score, c)))
+            .collect();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches.into_iter().map(|(_, c)| c.clone()).collect()
+    }
+
+    /// Projects passing the filter tokens of the search query and fuzzy-matching its text on
+    /// their name, best match first, together with the matched char indices. Frecent
+    /// projects get a capped boost on top of their match score; equal ranks keep the order
+    /// of the current view. Every project list action goes through this.
+    pub fn filtered_projects(&self) -> Vec<(&ProjectInfo, Vec<usize>)> {
+        let query = Query::parse(&self.search_query);
+        let mut projects: Vec<&ProjectInfo> = self
+            .projects
+            .iter()
+            .filter(|p| query.matches(p, self.is_favorite(&p.path)))
+            .collect();
+        self.sort_projects(&mut projects);
+        let mut matches: Vec<(&ProjectInfo, FuzzyMatch)> = projects
+            .into_iter()
+            .filter_map(|p| fuzzy_match(&query.text, &p.name).map(|m| (p, m)))
+            .collect();
+        if !self.search_query.is_empty() {
+            for (p, m) in &mut matches {
+                m.score += ((self.frecency(&p.path) * FRECENCY_BOOST) as i64).min(MAX_FRECENCY_BOOST);
+            }
+        }
+        matches.sort_by_key(|(_, m)| Reverse(m.score));
+        matches.into_iter().map(|(p, m)| (p, m.indices)).collect()
+    }
+
+    /// Key of the current project view in `Config::sort_orders`.
+    fn sort_view(&self) -> &'static str {
+        match self.mode {
+            AppMode::Favorites => "favorites",
+            AppMode::Recent => "recent",
+            AppMode::TopProjects => "top",
+            _ => "projects",
+        }
+    }
+
+    pub fn sort_order(&self) -> SortOrder {
+        let view = self.sort_view();
+        self.config
+            .sort_orders
+            .get(view)
+            .copied()
+            .unwrap_or(match view {
+                "recent" => SortOrder::LastOpened,
+                "top" => SortOrder::Frecency,
+                _ => SortOrder::Name,
+            })
+    }
+
+    /// Switches the current view to the next sort order and remembers it.
+    pub fn cycle_sort_order(&mut self) {
+        let order = self.sort_order().next();
+        self.config
+            .sort_orders
+            .insert(self.sort_view().to_string(), order);
+        let _ = self.save_config();
+        self.project_state.select(if self.projects.is_empty() {
+            None
         } else {
-            self.categories
-                .iter()
-                .filter(|c| c.to_lowercase().contains(&self.search_query.to_lowercase()))
-                .cloned()
-                .collect()
+            Some(0)
+        });
+        self.status_message = Some((format!("Sorted by {}", order.label()), Instant::now()));
+    }
+
+    /// Sorts by the current view's order. Time-based orders put the newest first and
+    /// projects without a value last.
+    fn sort_projects(&self, projects: &mut [&ProjectInfo]) {
+        let name = |p: &ProjectInfo| p.name.to_lowercase();
+        let opened = |p: &ProjectInfo| {
+            p.last_opened.or_else(|| {
+                self.config
+                    .history
+                    .get(p.path.to_string_lossy().as_ref())
+                    .map(|s| s.last_opened)
+            })
+        };
+        match self.sort_order() {
+            SortOrder::Name => projects.sort_by_cached_key(|p| name(p)),
+            SortOrder::Modified => projects.sort_by_key(|p| Reverse(p.modified)),
+            SortOrder::LastCommit => projects.sort_by_key(|p| Reverse(p.last_commit)),
+            SortOrder::DirtyFirst => projects.sort_by_cached_key(|p| (!p.has_changes, name(p))),
+            SortOrder::Language => projects.sort_by_cached_key(|p| {
+                (p.language.is_none(), p.language.clone(), name(p))
+            }),
+            SortOrder::LastOpened => projects.sort_by_cached_key(|p| Reverse(opened(p))),
+            SortOrder::Frecency => projects.sort_by_cached_key(|p| {
+                Reverse((self.frecency(&p.path) * 1000.0) as u64)
+            }),
+        }
+    }
+
+    /// The highlighted row of the (filtered) project table.
+    pub fn selected_project(&self) -> Option<ProjectInfo> {
+        let i = self.project_state.selected()?;
+        self.filtered_projects().get(i).map(|(p, _)| (*p).clone())
+    }
+
+    pub fn add_root(&mut self, input: &str) -> Result<()> {
+        // Accepts either `path` or `name=path`.
+        let (name, path) = match input.split_once('=') {
+            Some((name, path)) => (Some(name.trim()), path.trim()),
+            None => (None, input.trim()),
+        };
+        if !Path::new(path).is_dir() {
+            return Err(IdeaError::Project(format!("Path '{}' does not exist", path)));
+        }
+        if self.config.roots.iter().any(|r| r.path == path) {
+            return Err(IdeaError::Project(format!("'{}' is already a root", path)));
+        }
+        let name = match name {
+            Some(n) if !n.is_empty() && !self.config.roots.iter().any(|r| r.name == n) => {
+                n.to_string()
+            }
+            _ => root_name_for(path, &self.config.roots),
+        };
+        self.config.roots.push(ProjectRoot {
+            name: name.clone(),
+            path: path.to_string(),
+        });
+        self.root_state.select(Some(self.config.roots.len() - 1));
+        self.save_config()?;
+        self.status_message = Some((format!("Added root {}!", name), Instant::now()));
+        Ok(())
+    }
+
+    pub fn remove_root(&mut self) {
+        if let Some(i) = self.root_state.selected()
+            && i < self.config.roots.len()
+        {
+            let root = self.config.roots.remove(i);
+            self.root_state.select(if self.config.roots.is_empty() {
+                None
+            } else {
+                Some(i.min(self.config.roots.len() - 1))
+            });
+            let _ = self.save_config();
+            self.status_message = Some((format!("Removed root {}", root.name), Instant::now()));
+        }
+    }
+
+    /// Moves the selected root one step up (`-1`) or down (`1`).
+    pub fn move_root(&mut self, offset: isize) {
+        if let Some(i) = self.root_state.selected() {
+            let target = i as isize + offset;
+            if target >= 0 && (target as usize) < self.config.roots.len() {
+                self.config.roots.swap(i, target as usize);
+                self.root_state.select(Some(target as usize));
+                let _ = self.save_config();
+            }
         }
     }
 
@@ -517,13 +1209,11 @@ impl App {
                 };
                 self.category_state.select(Some(i));
             }
-            AppMode::ProjectSelection | AppMode::Favorites | AppMode::Recent => {
-                let query = self.search_query.to_lowercase();
-                let len = self
-                    .projects
-                    .iter()
-                    .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-                    .count();
+            AppMode::ProjectSelection
+            | AppMode::Favorites
+            | AppMode::Recent
+            | AppMode::TopProjects => {
+                let len = self.filtered_projects().len();
                 if len == 0 {
                     return;
                 }
@@ -556,6 +1246,71 @@ impl App {
                 };
                 self.branch_state.select(Some(i));
             }
+            AppMode::CloneOptions => {
+                let len = CloneField::ALL.len();
+                let i = match self.clone_field_state.selected() {
+                    Some(i) => {
+                        if i >= len - 1 {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.clone_field_state.select(Some(i));
+            }
+            AppMode::Cloning => {
+                let len = self.clones.len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.clone_state.selected() {
+                    Some(i) => {
+                        if i >= len - 1 {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.clone_state.select(Some(i));
+            }
+            AppMode::ManageRoots => {
+                let len = self.config.roots.len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.root_state.selected() {
+                    Some(i) => {
+                        if i >= len - 1 {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.root_state.select(Some(i));
+            }
+            AppMode::OpenWith | AppMode::IdeSetup => {
+                let len = self.ide_items.len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.ide_state.selected() {
+                    Some(i) => {
+                        if i >= len - 1 {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.ide_state.select(Some(i));
+            }
             _ => {}
         }
     }
@@ -566,34 +1321,102 @@ impl App {
                 let i = match self.menu_state.selected() {
                     Some(i) => {
                         if i == 0 {
-                            self.menu_items.len() - 1
+                            self.menu_items.len() - 1
+                        } else {
+                            i - 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.menu_state.select(Some(i));
+            }
+            AppMode::ThemeSelection => {
+                let i = match self.theme_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            self.theme_items.len() - 1
+                        } else {
+                            i - 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.theme_state.select(Some(i));
+            }
+            AppMode::CategorySelection | AppMode::CloneCategory => {
+                let len = self.get_filtered_categories().len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.category_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            len - 1
+                        } else {
+                            i - 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.category_state.select(Some(i));
+            }
+            AppMode::ProjectSelection
+            | AppMode::Favorites
+            | AppMode::Recent
+            | AppMode::TopProjects => {
+                let len = self.filtered_projects().len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.project_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            len - 1
+                        } else {
+                            i - 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.project_state.select(Some(i));
+            }
+            AppMode::BranchSelection => {
+                let len = self.branches.len();
+                if len == 0 {
+                    return;
+                }
+                let i = match self.branch_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            len - 1
                         } else {
                             i - 1
                         }
                     }
                     None => 0,
                 };
-                self.menu_state.select(Some(i));
+                self.branch_state.select(Some(i));
             }
-            AppMode::ThemeSelection => {
-                let i = match self.theme_state.selected() {
+            AppMode::CloneOptions => {
+                let len = CloneField::ALL.len();
+                let i = match self.clone_field_state.selected() {
                     Some(i) => {
                         if i == 0 {
-                            self.theme_items.len() - 1
+                            len - 1
                         } else {
                             i - 1
                         }
                     }
                     None => 0,
                 };
-                self.theme_state.select(Some(i));
+                self.clone_field_state.select(Some(i));
             }
-            AppMode::CategorySelection | AppMode::CloneCategory => {
-                let len = self.get_filtered_categories().len();
+            AppMode::Cloning => {
+                let len = self.clones.len();
                 if len == 0 {
                     return;
                 }
-                let i = match self.category_state.selected() {
+                let i = match self.clone_state.selected() {
                     Some(i) => {
                         if i == 0 {
                             len - 1
@@ -603,19 +1426,14 @@ impl App {
                     }
                     None => 0,
                 };
-                self.category_state.select(Some(i));
+                self.clone_state.select(Some(i));
             }
-            AppMode::ProjectSelection | AppMode::Favorites | AppMode::Recent => {
-                let query = self.search_query.to_lowercase();
-                let len = self
-                    .projects
-                    .iter()
-                    .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-                    .count();
+            AppMode::ManageRoots => {
+                let len = self.config.roots.len();
                 if len == 0 {
                     return;
                 }
-                let i = match self.project_state.selected() {
+                let i = match self.root_state.selected() {
                     Some(i) => {
                         if i == 0 {
                             len - 1
@@ -625,14 +1443,14 @@ impl App {
                     }
                     None => 0,
                 };
-                self.project_state.select(Some(i));
+                self.root_state.select(Some(i));
             }
-            AppMode::BranchSelection => {
-                let len = self.branches.len();
+            AppMode::OpenWith | AppMode::IdeSetup => {
+                let len = self.ide_items.len();
                 if len == 0 {
                     return;
                 }
-                let i = match self.branch_state.selected() {
+                let i = match self.ide_state.selected() {
                     Some(i) => {
                         if i == 0 {
                             len - 1
@@ -642,7 +1460,7 @@ impl App {
                     }
                     None => 0,
                 };
-                self.branch_state.select(Some(i));
+                self.ide_state.select(Some(i));
             }
             _ => {}
         }
@@ -660,42 +1478,50 @@ impl App {
                     self.mode = AppMode::Recent;
                 }
                 Some(2) => {
+                    self.load_top_projects();
+                    self.mode = AppMode::TopProjects;
+                }
+                Some(3) => {
                     self.load_categories();
-                    // If everything in base_dir is a project, skip category view
-                    let any_direct_projects = self.categories.iter().any(|c| {
-                        let path = PathBuf::from(&self.config.base_dir).join(c);
-                        Self::is_project(&path)
-                    });
+                    // With a single root that directly holds projects, skip the category view
+                    let roots = self.categories.iter().filter(|c| c.is_root()).count();
+                    let any_direct_projects = self
+                        .categories
+                        .iter()
+                        .any(|c| !c.is_root() && Self::is_project(&c.path));
 
-                    if any_direct_projects {
-                        self.load_projects(".".to_string());
+                    self.skipped_categories = roots == 1 && any_direct_projects;
+                    if self.skipped_categories {
+                        self.load_projects(self.categories[0].clone());
                         self.mode = AppMode::ProjectSelection;
                     } else {
                         self.mode = AppMode::CategorySelection;
                     }
                 }
-                Some(3) => {
+                Some(4) => {
                     self.input.clear();
                     self.mode = AppMode::InputUrl;
                 }
-                Some(4) => {
+                Some(5) => {
+                    self.input.clear();
+                    self.mode = AppMode::InputManifest;
+                }
+                Some(6) => {
                     self.pending_project = Some(ProjectInfo {
                         name: "IntelliJ IDEA".to_string(),
                         path: PathBuf::from("IDE"),
-                        git_branch: None,
-                        has_changes: false,
-                        language: None,
+                        ..ProjectInfo::default()
                     });
                     self.previous_mode = Some(AppMode::MainMenu);
                     self.mode = AppMode::ConfirmOpen;
                 }
-                Some(5) => {
+                Some(7) => {
                     self.mode = AppMode::ThemeSelection;
                 }
-                Some(6) => {
-                    self.input = self.config.base_dir.clone();
-                    self.mode = AppMode::ChangeBaseDir;
+                Some(8) => {
+                    self.mode = AppMode::ManageRoots;
                 }
+                Some(9) => self.start_ide_setup(),
                 _ => {}
             },
             AppMode::ThemeSelection => {
@@ -705,20 +1531,18 @@ impl App {
                     self.mode = AppMode::MainMenu;
                 }
             }
-            AppMode::ChangeBaseDir => {
-                if !self.input.is_empty() {
-                    let new_path = PathBuf::from(&self.input);
-                    if new_path.exists() {
-                        self.config.base_dir = self.input.clone();
-                        let _ = self.save_config();
-                        self.status_message = Some((
-                            format!("Base directory updated to {}!", self.input),
-                            Instant::now(),
-                        ));
-                        self.mode = AppMode::MainMenu;
-                    } else {
-                        self.status_message =
-                            Some(("Error: Path does not exist!".to_string(), Instant::now()));
+            AppMode::AddRoot if !self.input.is_empty() => {
+                let input = self.input.clone();
+                match self.add_root(&input) {
+                    Ok(()) => {
+                        self.input.clear();
+                        self.mode = AppMode::ManageRoots;
+                        if self.needs_ide_setup() {
+                            self.start_ide_setup();
+                        }
+                    }
+                    Err(e) => {
+                        self.status_message = Some((format!("Error: {}", e), Instant::now()));
                     }
                 }
             }
@@ -732,31 +1556,40 @@ impl App {
                     self.search_query.clear();
                 }
             }
-            AppMode::ProjectSelection | AppMode::Favorites | AppMode::Recent => {
-                let query = self.search_query.to_lowercase();
-                let filtered: Vec<&ProjectInfo> = self
-                    .projects
-                    .iter()
-                    .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-                    .collect();
-                if let Some(i) = self.project_state.selected() && i < filtered.len() {
-                    let proj = filtered[i];
+            AppMode::ProjectSelection
+            | AppMode::Favorites
+            | AppMode::Recent
+            | AppMode::TopProjects => {
+                if let Some(proj) = self.selected_project() {
                     self.pending_project = Some(ProjectInfo {
-                        name: proj.name.clone(),
-                        path: proj.path.clone(),
-                        git_branch: None,
-                        has_changes: false,
-                        language: None,
+                        name: proj.name,
+                        path: proj.path,
+                        language: proj.language,
+                        ..ProjectInfo::default()
                     });
                     self.previous_mode = Some(self.mode.clone());
                     self.mode = AppMode::ConfirmOpen;
                 }
             }
             AppMode::InputUrl => {
-                if !self.input.is_empty() {
-                    self.load_categories();
-                    self.mode = AppMode::CloneCategory;
-                }
+                let url = giturl::parse(&self.input)?;
+                self.clone_options = CloneOptions {
+                    name: url.name,
+                    ..CloneOptions::default()
+                };
+                self.clone_field_state.select(Some(0));
+                self.mode = AppMode::CloneOptions;
+            }
+            AppMode::CloneOptions => {
+                // Catch a bad depth or name before the category is picked.
+                self.clone_options.request(&self.input, Path::new(""))?;
+                self.load_categories();
+                self.mode = AppMode::CloneCategory;
+            }
+            AppMode::InputManifest => {
+                let manifest = Manifest::load(Path::new(self.input.trim()))?;
+                self.input.clear();
+                self.clone_manifest(&manifest);
             }
             AppMode::CloneCategory => {
                 let filtered = self.get_filtered_categories();
@@ -767,14 +1600,26 @@ impl App {
                     self.search_query.clear();
                 }
             }
-            AppMode::BranchSelection => {
-                if let Some(i) = self.branch_state.selected() && i < self.branches.len() {
-                    let branch = self.branches[i].clone();
-                    if let Some(proj) = self.pending_project.clone() {
-                        self.switch_branch(&branch, &proj.path)?;
-                        // After switching branch, proceed to open the project
-                        self.mode = AppMode::ConfirmOpen;
+            AppMode::NewBranch => self.create_branch()?,
+            AppMode::BranchSelection => self.start_checkout(true),
+            AppMode::IdeSetup => self.save_ide_setup(),
+            AppMode::OpenWith => {
+                if let Some(i) = self.ide_state.selected()
+                    && i < self.ide_items.len()
+                    && let Some(proj) = self.pending_project.clone()
+                {
+                    let ide = self.ide_items[i].clone();
+                    // Remember the choice unless it matches what would be picked anyway.
+                    if proj.path != Path::new("IDE") {
+                        let key = proj.path.to_str().unwrap_or("").to_string();
+                        self.config.project_ides.remove(&key);
+                        if self.ide_for(&proj.path, proj.language.as_deref()) != ide {
+                            self.config.project_ides.insert(key, ide.name.clone());
+                        }
+                        let _ = self.save_config();
                     }
+                    self.pending_ide = Some(ide);
+                    self.execute_pending_open()?;
                 }
             }
             _ => {}
@@ -782,73 +1627,367 @@ impl App {
         Ok(())
     }
 
+    /// Launches the pending project. On failure the project, and the confirm or open-with
+    /// screen, stay as they were so the launch can be retried with another IDE.
     pub fn execute_pending_open(&mut self) -> Result<()> {
-        if let Some(proj) = self.pending_project.take() {
-            if proj.name == "IntelliJ IDEA" {
-                self.spawn_process(vec![])?;
-                self.status_message =
-                    Some(("Opening IntelliJ IDEA...".to_string(), Instant::now()));
+        if let Some(proj) = self.pending_project.clone() {
+            let ide = self
+                .pending_ide
+                .take()
+                .unwrap_or_else(|| self.ide_for(&proj.path, proj.language.as_deref()));
+            if proj.path == Path::new("IDE") {
+                self.spawn_process(&ide, vec![])?;
+                self.status_message = Some((format!("Opening {}...", ide.name), Instant::now()));
             } else {
                 let path_str = proj.path.to_str().unwrap_or("").to_string();
-                let name = proj.name.clone();
-                self.add_to_recent(path_str.clone());
-                self.spawn_process(vec![path_str])?;
-                self.status_message = Some((format!("Launched {}!", name), Instant::now()));
+                self.spawn_process(&ide, vec![path_str.clone()])?;
+                // Only launches that started count towards recent projects and frecency.
+                self.add_to_recent(path_str);
+                self.status_message = Some((
+                    format!("Launched {} in {}!", proj.name, ide.name),
+                    Instant::now(),
+                ));
             }
+            self.pending_project = None;
         }
         self.mode = self.previous_mode.take().unwrap_or(AppMode::MainMenu);
         Ok(())
     }
 
-    pub fn clone_repo(&mut self, category: String) -> Result<()> {
-        let clone_dir = PathBuf::from(&self.config.base_dir).join(&category);
-        let url = self.input.clone();
-        let project_name = url
-            .split('/')
-            .next_back()
-            .and_then(|s| s.strip_suffix(".git").or(Some(s)))
-            .unwrap_or("new-project");
-        self.status_message = Some((format!("Cloning {}...", project_name), Instant::now()));
-        let mut command = process::Command::new("gh");
-        command
-            .arg("repo")
-            .arg("clone")
-            .arg(&url)
-            .arg("--")
-            .arg("--quiet")
-            .current_dir(&clone_dir)
-            .stdout(process::Stdio::null())
-            .stderr(process::Stdio::null());
-        let status = match command.status() {
-            Ok(s) if s.success() => Ok(s),
-            _ => process::Command::new("git")
-                .arg("clone")
-                .arg("--quiet")
-                .arg(&url)
-                .current_dir(&clone_dir)
-                .stdout(process::Stdio::null())
-                .stderr(process::Stdio::null())
-                .status()
-                .map_err(|e| IdeaError::Git(e.to_string())),
-        }?;
-        if status.success() {
-            let project_path = clone_dir.join(project_name);
-            let path_str = project_path.to_str().unwrap_or("").to_string();
-            self.add_to_recent(path_str.clone());
-            self.spawn_process(vec![path_str])?;
+    /// The clone of the URL in `input` into `category`, as set up in the options form.
+    pub fn clone_request(&self, category: &Category) -> Result<CloneRequest> {
+        self.clone_options.request(&self.input, &category.path)
+    }
+
+    pub fn selected_clone_field(&self) -> Option<CloneField> {
+        self.clone_field_state
+            .selected()
+            .and_then(|i| CloneField::ALL.get(i).copied())
+    }
+
+    /// Types `c` into the highlighted text field, or toggles the highlighted checkbox on
+    /// space.
+    pub fn clone_option_input(&mut self, c: char) {
+        let Some(field) = self.selected_clone_field() else {
+            return;
+        };
+        match self.clone_options.text_mut(field) {
+            Some(text) => text.push(c),
+            None if c == ' ' => self.clone_options.toggle(field),
+            None => {}
+        }
+    }
+
+    pub fn clone_option_backspace(&mut self) {
+        if let Some(field) = self.selected_clone_field()
+            && let Some(text) = self.clone_options.text_mut(field)
+        {
+            text.pop();
+        }
+    }
+
+    /// Starts cloning the URL in `input` in the background and shows the clone progress.
+    pub fn clone_repo(&mut self, category: Category) -> Result<()> {
+        let request = self.clone_request(&category)?;
+        if request.target.exists() {
+            return Err(IdeaError::Project(format!(
+                "{} already exists",
+                request.target.display()
+            )));
+        }
+        self.start_clone(request);
+        self.input.clear();
+        self.previous_mode = Some(AppMode::MainMenu);
+        self.mode = AppMode::Cloning;
+        Ok(())
+    }
+
+    /// Starts a background clone job and returns its id.
+    fn start_clone(&mut self, request: CloneRequest) -> usize {
+        let id = self.next_clone_id;
+        self.next_clone_id += 1;
+        self.clones.push(CloneJob::start(id, request, self.clone_tx.clone()));
+        self.clone_state.select(Some(self.clones.len() - 1));
+        id
+    }
+
+    /// Starts cloning every manifest entry whose folder doesn't exist yet, a few at a time.
+    /// Progress is tracked in `manifest_batch` and summarized by `poll_clones` once the
+    /// last clone finishes.
+    pub fn clone_manifest(&mut self, manifest: &Manifest) {
+        self.load_categories();
+        let mut batch = Batch::default();
+        let mut targets = HashSet::new();
+        for entry in &manifest.repos {
+            let request = self.manifest_category(&entry.category).and_then(|category| {
+                let name = match &entry.name {
+                    Some(name) => name.clone(),
+                    None => giturl::parse(&entry.url)?.name,
+                };
+                let options = CloneOptions {
+                    name,
+                    branch: entry.branch.clone().unwrap_or_default(),
+                    open: false,
+                    ..CloneOptions::default()
+                };
+                options.request(&entry.url, &category.path)
+            });
+            match request {
+                Ok(request) => {
+                    let label = format!("{}/{}", entry.category, request.name());
+                    if !targets.insert(request.target.clone()) {
+                        let message =
+                            format!("duplicate target {}", request.target.display());
+                        batch.record(label, Outcome::Failed(message));
+                    } else if request.target.exists() {
+                        batch.record(label, Outcome::Present);
+                    } else {
+                        batch.queue(label, request);
+                    }
+                }
+                Err(IdeaError::Project(message)) => {
+                    batch.record(entry.url.clone(), Outcome::Failed(message))
+                }
+                Err(e) => batch.record(entry.url.clone(), Outcome::Failed(e.to_string())),
+            }
+        }
+        let finished = batch.is_finished();
+        self.manifest_batch = Some(batch);
+        if finished {
+            self.report_manifest();
+        } else {
+            self.start_queued_clones();
+            self.previous_mode = Some(AppMode::MainMenu);
+            self.mode = AppMode::Cloning;
+        }
+    }
+
+    /// The category a manifest entry names. A missing one is created: `root/name` inside
+    /// that root, anything else inside the first root.
+    fn manifest_category(&mut self, label: &str) -> Result<Category> {
+        if let Some(category) = self.find_category(label) {
+            return Ok(category);
+        }
+        let (root, name) = match label.split_once('/') {
+            Some((root, name)) if self.config.roots.iter().any(|r| r.name == root) => (
+                self.config.roots.iter().find(|r| r.name == root).cloned(),
+                name,
+            ),
+            _ => (self.config.roots.first().cloned(), label),
+        };
+        let root =
+            root.ok_or_else(|| IdeaError::Project("No project roots configured".to_string()))?;
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(IdeaError::Project(format!("Invalid category '{}'", label)));
+        }
+        let category = Category {
+            root: root.name,
+            name: name.to_string(),
+            path: PathBuf::from(&root.path).join(name),
+        };
+        fs::create_dir_all(&category.path).map_err(|e| {
+            IdeaError::Project(format!("Can't create {}: {}", category.path.display(), e))
+        })?;
+        self.categories.push(category.clone());
+        Ok(category)
+    }
+
+    /// Starts queued manifest clones until `MAX_MANIFEST_CLONES` are running.
+    fn start_queued_clones(&mut self) {
+        while let Some((label, request)) = self
+            .manifest_batch
+            .as_mut()
+            .and_then(|b| b.next_queued(MAX_MANIFEST_CLONES))
+        {
+            let id = self.start_clone(request);
+            if let Some(batch) = &mut self.manifest_batch {
+                batch.add_job(id, label);
+            }
+        }
+    }
+
+    /// A manifest of every git project that sits directly in a category, with its remote
+    /// URL and current branch, so `clone --manifest` can recreate the tree elsewhere.
+    /// Projects that can't be listed come back separately with the reason.
+    pub fn export_manifest(&mut self) -> (Manifest, Vec<(PathBuf, String)>) {
+        self.load_all_projects();
+        self.wait_for_git_status();
+        let mut manifest = Manifest::default();
+        let mut skipped = Vec::new();
+        for p in &self.projects {
+            if !p.path.join(".git").exists() {
+                continue;
+            }
+            let Some(category) = self
+                .categories
+                .iter()
+                .find(|c| Some(c.path.as_path()) == p.path.parent())
+            else {
+                skipped.push((p.path.clone(), "not directly inside a category".to_string()));
+                continue;
+            };
+            let url = match self.git_backend.remote_url(&p.path) {
+                Ok(Some(url)) => url,
+                Ok(None) => {
+                    skipped.push((p.path.clone(), "no remote".to_string()));
+                    continue;
+                }
+                Err(e) => {
+                    skipped.push((p.path.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            let folder = p
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // Only recorded when cloning the URL wouldn't pick the same folder name.
+            let name = match giturl::parse(&url) {
+                Ok(parsed) if parsed.name == folder => None,
+                _ => Some(folder),
+            };
+            manifest.repos.push(ManifestEntry {
+                url,
+                category: category.label(),
+                branch: p.git.head.clone().filter(|_| !p.git.detached),
+                name,
+            });
+        }
+        (manifest, skipped)
+    }
+
+    /// Shows the results of the finished manifest clone: a popup listing failures, or a
+    /// status message when everything worked.
+    fn report_manifest(&mut self) {
+        let Some(batch) = &self.manifest_batch else {
+            return;
+        };
+        if batch.failed() > 0 {
+            self.error = Some(IdeaError::Manifest(batch.report()));
+        } else {
             self.status_message = Some((
-                format!("Cloned and opened {}!", project_name),
+                format!("Manifest: {}", batch.summary()),
                 Instant::now(),
             ));
+        }
+        if self.mode == AppMode::InputManifest {
             self.mode = AppMode::MainMenu;
-        } else {
-            return Err(IdeaError::CloneFailed(project_name.to_string()));
         }
+        self.reload_current_view();
+    }
+
+    /// Applies progress from clone jobs. Finished clones are opened in their IDE and
+    /// failures are shown in the error popup.
+    pub fn poll_clones(&mut self) {
+        while let Ok(update) = self.clone_rx.try_recv() {
+            self.apply_clone_update(update);
+        }
+    }
+
+    /// Blocks until the next clone update arrives and applies it, for the CLI.
+    pub fn wait_for_clone_update(&mut self) {
+        if let Ok(update) = self.clone_rx.recv() {
+            self.apply_clone_update(update);
+        }
+    }
+
+    fn apply_clone_update(&mut self, update: CloneUpdate) {
+        let Some(job) = self.clones.iter_mut().find(|j| j.id == update.id) else {
+            return;
+        };
+        job.apply(update.event);
+        let request = job.request.clone();
+        let state = job.state.clone();
+        // Manifest clones are reported together once the last one finishes.
+        if let Some(batch) = &mut self.manifest_batch
+            && batch.finish(update.id, &state).is_some()
+        {
+            self.start_queued_clones();
+            if self.manifest_batch.as_ref().is_some_and(|b| b.is_finished()) {
+                self.report_manifest();
+            }
+            return;
+        }
+        match state {
+            CloneState::Done if request.open => {
+                if let Err(e) = self.open_cloned(&request) {
+                    self.error = Some(e);
+                }
+                self.reload_current_view();
+            }
+            CloneState::Done => {
+                self.status_message = Some((
+                    format!("Cloned {}!", request.name()),
+                    Instant::now(),
+                ));
+                self.reload_current_view();
+            }
+            CloneState::Failed(message) => {
+                self.error = Some(IdeaError::CloneFailed(format!(
+                    "{}\n\n{}",
+                    request.name(),
+                    message
+                )));
+            }
+            CloneState::Cancelled => {
+                self.status_message = Some((
+                    format!("Cancelled cloning {}", request.name()),
+                    Instant::now(),
+                ));
+            }
+            CloneState::Running => {}
+        }
+    }
+
+    /// Records a finished clone as recently opened and opens it in its IDE.
+    pub fn open_cloned(&mut self, request: &CloneRequest) -> Result<()> {
+        let project_path = &request.target;
+        let path_str = project_path.to_str().unwrap_or("").to_string();
+        let ide = self.ide_for(project_path, Self::detect_language(project_path).as_deref());
+        self.spawn_process(&ide, vec![path_str.clone()])?;
+        self.add_to_recent(path_str);
+        self.status_message = Some((
+            format!("Cloned and opened {}!", request.name()),
+            Instant::now(),
+        ));
         Ok(())
     }
 
-    fn spawn_process(&self, args: Vec<String>) -> Result<()> {
-        let mut command = process::Command::new(&self.config.idea_path);
+    pub fn cancel_clone(&mut self) {
+        if let Some(job) = self.clone_state.selected().and_then(|i| self.clones.get(i)) {
+            job.cancel();
+        }
+    }
+
+    /// Whether the app can quit right away. Quitting ends running clones half way, so with
+    /// any running the quit confirmation is shown instead.
+    pub fn request_quit(&mut self) -> bool {
+        self.confirm_quit = self.clones.iter().any(|j| j.is_running());
+        !self.confirm_quit
+    }
+
+    /// Cancels every running clone and waits until each has removed its partial folder.
+    pub fn cancel_all_clones(&mut self) {
+        if let Some(batch) = &mut self.manifest_batch {
+            batch.cancel_queued();
+        }
+        for job in self.clones.iter().filter(|j| j.is_running()) {
+            job.cancel();
+        }
+        while self.clones.iter().any(|j| j.is_running()) {
+            self.wait_for_clone_update();
+        }
+    }
+
+    /// Removes finished, failed and cancelled jobs from the clone list.
+    pub fn clear_finished_clones(&mut self) {
+        self.clones.retain(|j| j.is_running());
+        self.clone_state.select(if self.clones.is_empty() { None } else { Some(0) });
+    }
+
+    pub fn spawn_process(&self, ide: &IdeLauncher, args: Vec<String>) -> Result<()> {
+        let mut command = process::Command::new(&ide.path);
         for arg in args {
             command.arg(arg);
         }
@@ -866,7 +2005,9 @@ impl App {
             }
         }
 
-        command.spawn().map_err(|e| IdeaError::Spawn(e.to_string()))?;
+        command
+            .spawn()
+            .map_err(|e| IdeaError::Spawn(format!("{} ({}): {}", ide.name, ide.path, e)))?;
         Ok(())
     }
 
@@ -877,18 +2018,39 @@ impl App {
             AppMode::MainMenu => {}
             AppMode::CategorySelection
             | AppMode::InputUrl
+            | AppMode::InputManifest
             | AppMode::Favorites
             | AppMode::Recent
+            | AppMode::TopProjects
             | AppMode::ThemeSelection
-            | AppMode::ChangeBaseDir => self.mode = AppMode::MainMenu,
+            | AppMode::ManageRoots => self.mode = AppMode::MainMenu,
+            AppMode::IdeSetup => {
+                self.config.ide_setup_dismissed = true;
+                let _ = self.save_config();
+                self.mode = AppMode::MainMenu;
+            }
+            AppMode::AddRoot => self.mode = AppMode::ManageRoots,
+            AppMode::NewBranch => {
+                self.input.clear();
+                self.mode = AppMode::BranchSelection;
+            }
+            AppMode::ConfirmCheckout => {
+                self.pending_checkout = None;
+                self.mode = AppMode::BranchSelection;
+            }
             AppMode::ProjectSelection => {
-                if self.selected_category == Some(".".to_string()) {
+                if self.skipped_categories {
                     self.mode = AppMode::MainMenu;
                 } else {
                     self.mode = AppMode::CategorySelection;
                 }
             }
-            AppMode::CloneCategory => self.mode = AppMode::InputUrl,
+            AppMode::CloneOptions => self.mode = AppMode::InputUrl,
+            AppMode::CloneCategory => self.mode = AppMode::CloneOptions,
+            AppMode::Cloning => {
+                self.mode = self.previous_mode.take().unwrap_or(AppMode::MainMenu);
+            }
+            AppMode::OpenWith => self.mode = AppMode::ConfirmOpen,
             AppMode::ConfirmOpen | AppMode::Help | AppMode::BranchSelection => {
                 self.mode = self.previous_mode.take().unwrap_or(AppMode::MainMenu);
                 self.pending_project = None;
@@ -899,11 +2061,36 @@ impl App {
 }
 
 #[cfg(test)]
+// The language detection tests pass `&dir.path().to_path_buf()` where a `&Path` would do.
+#[allow(clippy::unnecessary_to_owned)]
 mod tests {
     use super::*;
+    use crate::git::tests::init_repo;
+    use crate::models::OpenStats;
     use std::fs;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_ide_setup_only_when_no_ide_works() {
+        let dir = tempdir().unwrap();
+        let mut config = Config {
+            idea_path: dir.path().join("missing/idea").to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        assert!(App::new(config.clone()).needs_ide_setup());
+
+        config.ides.push(IdeLauncher {
+            name: "PyCharm".to_string(),
+            path: dir.path().to_string_lossy().to_string(),
+        });
+        let app = App::new(config.clone());
+        assert!(app.any_ide_found() && !app.needs_ide_setup());
+
+        config.ides.clear();
+        config.ide_setup_dismissed = true;
+        assert!(!App::new(config).needs_ide_setup());
+    }
+
     #[test]
     fn test_detect_language_rust() {
         let dir = tempdir().unwrap();
@@ -986,4 +2173,389 @@ mod tests {
         let dir = tempdir().unwrap();
         assert_eq!(App::detect_language(&dir.path().to_path_buf()), None);
     }
+
+    #[test]
+    fn test_load_all_projects_walks_categories() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("work/api")).unwrap();
+        fs::write(dir.path().join("work/api/Cargo.toml"), "").unwrap();
+        fs::create_dir_all(dir.path().join("tool")).unwrap();
+        fs::write(dir.path().join("tool/go.mod"), "").unwrap();
+        let config = Config {
+            roots: vec![ProjectRoot {
+                name: "dev".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+            }],
+            ..Config::default()
+        };
+        let mut app = App::new(config);
+        app.cache = ProjectCache::default();
+        app.load_all_projects();
+        let mut names: Vec<&str> = app.projects.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["tool", "work/api"]);
+        assert_eq!(
+            app.find_project("API").map(|p| p.path),
+            Some(dir.path().join("work/api"))
+        );
+    }
+
+    #[test]
+    fn test_roots_are_top_level_categories() {
+        let work = tempdir().unwrap();
+        let oss = tempdir().unwrap();
+        fs::create_dir_all(work.path().join("client/api")).unwrap();
+        fs::write(work.path().join("client/api/pom.xml"), "").unwrap();
+        fs::create_dir_all(oss.path().join("ratatui")).unwrap();
+        fs::write(oss.path().join("ratatui/Cargo.toml"), "").unwrap();
+        let config = Config {
+            base_dir: work.path().to_string_lossy().to_string(),
+            roots: vec![ProjectRoot {
+                name: "oss".to_string(),
+                path: oss.path().to_string_lossy().to_string(),
+            }],
+            ..Config::default()
+        };
+        let mut app = App::new(config);
+        app.cache = ProjectCache::default();
+        assert!(app.config.base_dir.is_empty());
+        assert_eq!(app.config.roots.len(), 2);
+
+        app.load_categories();
+        let root_name = app.config.roots[0].name.clone();
+        let labels: Vec<String> = app.categories.iter().map(|c| c.label()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                root_name.clone(),
+                format!("{}/client", root_name),
+                "oss".to_string(),
+                "oss/ratatui".to_string(),
+            ]
+        );
+        assert_eq!(
+            app.find_category("client").map(|c| c.path),
+            Some(work.path().join("client"))
+        );
+
+        app.load_all_projects();
+        let mut names: Vec<&str> = app.projects.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["client/api", "ratatui"]);
+    }
+
+    #[test]
+    fn test_ide_for_prefers_override_then_language() {
+        let mut config = Config {
+            ides: vec![
+                IdeLauncher {
+                    name: "RustRover".to_string(),
+                    path: "rustrover".to_string(),
+                },
+                IdeLauncher {
+                    name: "PyCharm".to_string(),
+                    path: "pycharm".to_string(),
+                },
+            ],
+            ..Config::default()
+        };
+        config
+            .project_ides
+            .insert("/dev/tool".to_string(), "PyCharm".to_string());
+        let app = App::new(config);
+        let name = |path: &str, lang: Option<&str>| app.ide_for(Path::new(path), lang).name;
+        assert_eq!(name("/dev/api", Some("Rust")), "RustRover");
+        assert_eq!(name("/dev/svc", Some("Go")), DEFAULT_IDE);
+        assert_eq!(name("/dev/tool", Some("Rust")), "PyCharm");
+        assert_eq!(app.available_ides().len(), 3);
+    }
+
+    #[test]
+    fn test_git_status_is_filled_in_by_workers() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("repo/.git")).unwrap();
+        fs::create_dir_all(dir.path().join("plain")).unwrap();
+        fs::write(dir.path().join("plain/go.mod"), "").unwrap();
+        let config = Config {
+            roots: vec![ProjectRoot {
+                name: "dev".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+            }],
+            ..Config::default()
+        };
+        let mut app = App::new(config);
+        app.cache = ProjectCache::default();
+        app.load_categories();
+        app.load_projects(app.categories[0].clone());
+        let loading: Vec<bool> = app.projects.iter().map(|p| p.git_loading).collect();
+        assert_eq!(loading, vec![false, true]);
+        app.wait_for_git_status();
+        assert!(app.projects.iter().all(|p| !p.git_loading));
+    }
+
+    #[test]
+    fn test_cached_git_status_is_revalidated() {
+        let dir = tempdir().unwrap();
+        init_repo(&dir.path().join("repo"), &[]);
+        let mut app = App::new(Config {
+            roots: vec![ProjectRoot {
+                name: "dev".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+            }],
+            ..Config::default()
+        });
+        app.cache = ProjectCache::default();
+        app.load_categories();
+        app.load_projects(app.categories[0].clone());
+        app.wait_for_git_status();
+        assert!(!app.projects[0].has_changes);
+
+        // Leaves HEAD and the index alone, so the cache entry stays fresh.
+        fs::write(dir.path().join("repo/new.txt"), "").unwrap();
+        app.load_projects(app.categories[0].clone());
+        assert!(app.projects[0].git_loading);
+        assert!(app.projects[0].git_branch.is_some());
+        app.wait_for_git_status();
+        assert!(app.projects[0].has_changes);
+    }
+
+    #[test]
+    fn test_top_projects_and_search_rank_by_frecency() {
+        let dir = tempdir().unwrap();
+        for name in ["daily", "old"] {
+            fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        let now = now_secs();
+        let key = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        let mut config = Config::default();
+        for (name, count, age) in [
+            ("daily", 3, 60),
+            ("old", 50, 90 * 24 * 60 * 60),
+            ("missing", 99, 0),
+        ] {
+            config.history.insert(
+                key(name),
+                OpenStats {
+                    count,
+                    last_opened: now - age,
+                },
+            );
+        }
+        let mut app = App::new(config);
+        app.cache = ProjectCache::default();
+
+        app.load_top_projects();
+        let names: Vec<&str> = app.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["old", "daily"]);
+
+        // "gone-api" matches "a" at a word boundary, but "daily" was opened just now.
+        app.projects = ["daily", "old", "gone-api"]
+            .iter()
+            .map(|n| ProjectInfo {
+                name: n.to_string(),
+                path: dir.path().join(n),
+                ..ProjectInfo::default()
+            })
+            .collect();
+        app.search_query = "a".to_string();
+        let ranked: Vec<&str> = app
+            .filtered_projects()
+            .iter()
+            .map(|(p, _)| p.name.as_str())
+            .collect();
+        assert_eq!(ranked, vec!["daily", "gone-api"]);
+    }
+
+    #[test]
+    fn test_sort_order_is_remembered_per_view() {
+        let project = |name: &str, dirty: bool, commit: Option<u64>| ProjectInfo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/dev/{}", name)),
+            git_branch: Some("main".to_string()),
+            has_changes: dirty,
+            last_commit: commit,
+            ..ProjectInfo::default()
+        };
+        let mut config = Config::default();
+        config
+            .sort_orders
+            .insert("projects".to_string(), SortOrder::DirtyFirst);
+        config
+            .sort_orders
+            .insert("favorites".to_string(), SortOrder::LastCommit);
+        let mut app = App::new(config);
+        app.projects = vec![
+            project("alpha", false, Some(10)),
+            project("beta", true, None),
+            project("gamma", false, Some(20)),
+        ];
+        let names = |app: &App| -> Vec<String> {
+            app.filtered_projects()
+                .iter()
+                .map(|(p, _)| p.name.clone())
+                .collect()
+        };
+
+        app.mode = AppMode::ProjectSelection;
+        assert_eq!(names(&app), vec!["beta", "alpha", "gamma"]);
+        app.mode = AppMode::Favorites;
+        assert_eq!(names(&app), vec!["gamma", "alpha", "beta"]);
+        app.mode = AppMode::Recent;
+        assert_eq!(app.sort_order(), SortOrder::LastOpened);
+        assert_eq!(SortOrder::Frecency.next(), SortOrder::Name);
+    }
+
+    #[test]
+    fn test_failed_launch_keeps_pending_project() {
+        let dir = tempdir().unwrap();
+        let mut app = App::new(Config::default());
+        let project = ProjectInfo {
+            name: "api".to_string(),
+            path: dir.path().to_path_buf(),
+            ..ProjectInfo::default()
+        };
+        app.pending_project = Some(project);
+        app.pending_ide = Some(IdeLauncher {
+            name: "Missing".to_string(),
+            path: dir.path().join("missing-ide").to_string_lossy().to_string(),
+        });
+        app.previous_mode = Some(AppMode::Favorites);
+        app.mode = AppMode::ConfirmOpen;
+
+        assert!(matches!(app.execute_pending_open(), Err(IdeaError::Spawn(_))));
+        assert_eq!(app.pending_project.map(|p| p.path), Some(dir.path().to_path_buf()));
+        assert_eq!(app.mode, AppMode::ConfirmOpen);
+        assert_eq!(app.previous_mode, Some(AppMode::Favorites));
+        assert!(app.config.recent_projects.is_empty() && app.config.history.is_empty());
+    }
+
+    #[test]
+    fn test_quit_with_running_clones_asks_then_cancels() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_repo(&source, &[]);
+
+        let mut app = App::new(Config::default());
+        assert!(app.request_quit());
+        let target = dir.path().join("copy");
+        app.start_clone(CloneRequest {
+            url: format!("file://{}", source.display()),
+            target: target.clone(),
+            ..CloneRequest::default()
+        });
+        assert!(!app.request_quit());
+        assert!(app.confirm_quit);
+
+        app.cancel_all_clones();
+        let job = &app.clones[0];
+        assert!(!job.is_running());
+        // The clone may have finished before the cancel reached it; it is never left half done.
+        match job.state {
+            CloneState::Cancelled => assert!(!target.exists()),
+            _ => assert_eq!(job.state, CloneState::Done),
+        }
+    }
+
+    #[test]
+    fn test_manifest_creates_missing_categories() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_repo(&source, &[]);
+        let root = |name: &str| ProjectRoot {
+            name: name.to_string(),
+            path: dir.path().join(name).to_string_lossy().to_string(),
+        };
+        let mut app = App::new(Config {
+            roots: vec![root("dev"), root("oss")],
+            ..Config::default()
+        });
+        let url = format!("file://{}", source.display());
+        let entry = |category: &str, name: &str| ManifestEntry {
+            url: url.clone(),
+            category: category.to_string(),
+            name: Some(name.to_string()),
+            ..ManifestEntry::default()
+        };
+        app.clone_manifest(&Manifest {
+            repos: vec![entry("oss/libs", "lib"), entry("work", "api"), entry("work", "web")],
+        });
+        while !app.manifest_batch.as_ref().unwrap().is_finished() {
+            app.wait_for_clone_update();
+        }
+
+        let batch = app.manifest_batch.as_ref().unwrap();
+        assert_eq!(batch.summary(), "3 cloned");
+        for path in ["oss/libs/lib", "dev/work/api", "dev/work/web"] {
+            assert!(dir.path().join(path).join(".git").exists(), "{} not cloned", path);
+        }
+    }
+
+    #[test]
+    fn test_manifest_refuses_duplicate_targets() {
+        let dir = tempdir().unwrap();
+        let alice = dir.path().join("alice/api");
+        let bob = dir.path().join("bob/api");
+        init_repo(&alice, &[]);
+        init_repo(&bob, &[]);
+        let mut app = App::new(Config {
+            roots: vec![ProjectRoot {
+                name: "dev".to_string(),
+                path: dir.path().join("dev").to_string_lossy().to_string(),
+            }],
+            ..Config::default()
+        });
+        let entry = |source: &Path| ManifestEntry {
+            url: format!("file://{}", source.display()),
+            category: "work".to_string(),
+            ..ManifestEntry::default()
+        };
+        app.clone_manifest(&Manifest {
+            repos: vec![entry(&alice), entry(&bob)],
+        });
+        while !app.manifest_batch.as_ref().unwrap().is_finished() {
+            app.wait_for_clone_update();
+        }
+
+        let batch = app.manifest_batch.as_ref().unwrap();
+        assert_eq!(batch.summary(), "1 cloned, 1 failed");
+        let target = dir.path().join("dev/work/api");
+        assert!(matches!(
+            &batch.results[0],
+            (label, Outcome::Failed(message))
+                if label == "work/api" && message.contains(&*target.to_string_lossy())
+        ));
+        assert!(target.join(".git").exists());
+        assert_eq!(app.clones.len(), 1);
+    }
+
+    #[test]
+    fn test_dirty_checkout_asks_then_stashes() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo(dir.path(), &[("a.txt", "one")]);
+        let oid = repo.head().unwrap().target().unwrap();
+        repo.branch("other", &repo.find_commit(oid).unwrap(), false)
+            .unwrap();
+        fs::write(dir.path().join("a.txt"), "changed").unwrap();
+
+        let mut app = App::new(Config::default());
+        app.cache = ProjectCache::default();
+        app.pending_project = Some(ProjectInfo {
+            name: "repo".to_string(),
+            path: dir.path().to_path_buf(),
+            ..ProjectInfo::default()
+        });
+        app.load_branches(dir.path()).unwrap();
+        app.mode = AppMode::BranchSelection;
+        app.branch_state
+            .select(app.branches.iter().position(|b| b.name == "other"));
+        app.on_enter().unwrap();
+        assert_eq!(app.mode, AppMode::ConfirmCheckout);
+
+        app.confirm_checkout(true);
+        assert!(app.error.is_none());
+        assert_eq!(app.mode, AppMode::ConfirmOpen);
+        let status = app.git_backend.status(dir.path()).unwrap();
+        assert_eq!(status.head.as_deref(), Some("other"));
+        assert!(!status.is_dirty());
+    }
 }
@@ -0,0 +1,182 @@
+use crate::error::{IdeaError, Result};
+use crate::git;
+use crate::models::{GitStatus, ProjectInfo};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Files whose modification times decide whether a cached entry is still valid, besides
+/// the git files in `ProjectCache::stamp`.
+const STAMP_FILES: [&str; 7] = [
+    "Cargo.toml",
+    "pom.xml",
+    "build.gradle",
+    "package.json",
+    "pyproject.toml",
+    "requirements.txt",
+    "go.mod",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub stamp: Vec<u64>,
+    pub language: Option<String>,
+    pub git_branch: Option<String>,
+    pub has_changes: bool,
+    #[serde(default)]
+    pub last_commit: Option<u64>,
+    #[serde(default)]
+    pub git: GitStatus,
+}
+
+/// Project metadata persisted in `cache.json` next to the confy config file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ProjectCache {
+    #[serde(default)]
+    entries: HashMap<String, CacheEntry>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl ProjectCache {
+    pub fn load() -> ProjectCache {
+        let Ok(path) = Self::file_path() else {
+            return ProjectCache::default();
+        };
+        let mut cache: ProjectCache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        cache.path = Some(path);
+        cache
+    }
+
+    fn file_path() -> Result<PathBuf> {
+        let config_path = confy::get_configuration_file_path("idea-tui", None)?;
+        Ok(config_path.with_file_name("cache.json"))
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string(self).map_err(|e| IdeaError::Unknown(e.to_string()))?;
+            fs::write(path, json)?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Modification times of `HEAD`, `index` and `FETCH_HEAD` (which changes on fetch and
+    /// moves the ahead/behind counts), then of `STAMP_FILES`. The git files are looked up
+    /// in the real git dir, since `.git` is only a pointer file in worktrees and submodules.
+    pub fn stamp(path: &Path) -> Vec<u64> {
+        let git_dir = git::git_dir(path);
+        [
+            git_dir.join("HEAD"),
+            git_dir.join("index"),
+            git::common_dir(&git_dir).join("FETCH_HEAD"),
+        ]
+        .into_iter()
+        .chain(STAMP_FILES.iter().map(|f| path.join(f)))
+        .map(|f| {
+            fs::metadata(f)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0)
+        })
+        .collect()
+    }
+
+    /// Returns the cached entry only if none of the stamp files changed since it was stored.
+    pub fn get(&self, path: &Path) -> Option<&CacheEntry> {
+        let entry = self.entries.get(path.to_string_lossy().as_ref())?;
+        (entry.stamp == Self::stamp(path)).then_some(entry)
+    }
+
+    pub fn insert(&mut self, info: &ProjectInfo) {
+        let entry = CacheEntry {
+            stamp: Self::stamp(&info.path),
+            language: info.language.clone(),
+            git_branch: info.git_branch.clone(),
+            has_changes: info.has_changes,
+            last_commit: info.last_commit,
+            git: info.git.clone(),
+        };
+        self.entries
+            .insert(info.path.to_string_lossy().to_string(), entry);
+        self.dirty = true;
+    }
+
+    pub fn invalidate(&mut self, path: &Path) {
+        if self.entries.remove(path.to_string_lossy().as_ref()).is_some() {
+            self.dirty = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::init_repo;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_entry_goes_stale_when_manifest_changes() {
+        let dir = tempdir().unwrap();
+        let mut cache = ProjectCache::default();
+        let mut info = ProjectInfo {
+            path: dir.path().to_path_buf(),
+            ..ProjectInfo::default()
+        };
+        cache.insert(&info);
+        assert!(cache.get(dir.path()).is_some());
+
+        fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        assert!(cache.get(dir.path()).is_none());
+
+        info.language = Some("Rust".to_string());
+        cache.insert(&info);
+        assert_eq!(
+            cache.get(dir.path()).and_then(|e| e.language.clone()),
+            Some("Rust".to_string())
+        );
+        cache.invalidate(dir.path());
+        assert!(cache.get(dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_worktree_entry_goes_stale_when_head_moves() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("main");
+        let repo = init_repo(&main, &[]);
+        let oid = repo.head().unwrap().target().unwrap();
+        let linked = dir.path().join("linked");
+        repo.worktree("linked", &linked, None).unwrap();
+        assert!(linked.join(".git").is_file());
+
+        let mut cache = ProjectCache::default();
+        cache.insert(&ProjectInfo {
+            path: linked.clone(),
+            ..ProjectInfo::default()
+        });
+        assert!(cache.get(&linked).is_some());
+
+        let worktree = git2::Repository::open(&linked).unwrap();
+        worktree
+            .branch("topic", &worktree.find_commit(oid).unwrap(), false)
+            .unwrap();
+        worktree.set_head("refs/heads/topic").unwrap();
+        assert!(cache.get(&linked).is_none());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::app::App;
+use crate::clone::{self, CloneOptions};
+use crate::error::{IdeaError, Result};
+use crate::giturl;
+use crate::manifest::Manifest;
+use crate::models::{Category, Config, ProjectInfo};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_derive::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "idea-tui", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List projects, optionally limited to a category (`root/category`, `category` or `root`)
+    List {
+        category: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Plain)]
+        format: OutputFormat,
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+    /// Open a project in its IDE by name
+    Open {
+        name: String,
+        /// Launcher name from `ides` to use instead of the automatic choice
+        #[arg(long)]
+        ide: Option<String>,
+    },
+    /// Clone a repository into a category and open it
+    Clone {
+        /// Repository URL, `owner/repo` or `gh:owner/repo` for GitHub
+        #[arg(required_unless_present = "manifest")]
+        url: Option<String>,
+        #[arg(short, long, required_unless_present = "manifest")]
+        category: Option<String>,
+        /// Branch to check out instead of the default branch
+        #[arg(short, long)]
+        branch: Option<String>,
+        /// Create a shallow clone with this many commits
+        #[arg(long)]
+        depth: Option<u32>,
+        /// Folder name, defaults to the repository name
+        #[arg(long)]
+        name: Option<String>,
+        /// Also clone submodules
+        #[arg(long)]
+        recurse_submodules: bool,
+        /// Only clone, don't open the project afterwards
+        #[arg(long)]
+        no_open: bool,
+        /// Clone every missing repository listed in a TOML manifest, in parallel
+        #[arg(
+            long,
+            conflicts_with_all = ["url", "category", "branch", "depth", "name", "recurse_submodules", "no_open"]
+        )]
+        manifest: Option<PathBuf>,
+    },
+    /// Add or remove favorites
+    Fav {
+        #[command(subcommand)]
+        action: FavAction,
+    },
+    /// Export the workspace as a manifest, or clone one
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestAction,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Aligned name and path columns
+    Plain,
+    /// A JSON array of project objects
+    Json,
+    /// name, path, branch, has_changes, language, favorite, recent; tab separated, with
+    /// tabs, newlines and backslashes in values escaped as `\t`, `\n` and `\\`
+    Tsv,
+}
+
+#[derive(Serialize)]
+struct ProjectRecord<'a> {
+    name: &'a str,
+    path: &'a Path,
+    git_branch: Option<&'a str>,
+    has_changes: bool,
+    language: Option<&'a str>,
+    favorite: bool,
+    recent: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FavAction {
+    /// Mark a project directory as favorite
+    Add { path: PathBuf },
+    /// Remove a project directory from favorites
+    Rm { path: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ManifestAction {
+    /// Write each git project's category, remote URL and branch as TOML
+    Export {
+        /// File to write instead of standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Clone the missing repositories of a manifest, like `clone --manifest`
+    Import { path: PathBuf },
+}
+
+pub fn run(command: Command, config: Config) -> Result<()> {
+    let mut app = App::new(config);
+    if let Some((msg, _)) = app.status_message.take() {
+        eprintln!("{}", msg);
+    }
+    // Only opening a project needs an IDE.
+    if matches!(command, Command::Open { .. }) && !app.any_ide_found() {
+        eprintln!(
+            "Warning: none of the configured IDEs were found! Run idea-tui to pick an installed IDE."
+        );
+    }
+    if !app.config.roots.iter().any(|r| Path::new(&r.path).exists()) {
+        return Err(IdeaError::Project(
+            "None of the configured project roots exist".to_string(),
+        ));
+    }
+
+    match command {
+        Command::List {
+            category,
+            format,
+            json,
+        } => {
+            match category {
+                Some(cat) => {
+                    let cat = resolve_category(&mut app, &cat)?;
+                    app.load_projects(cat);
+                }
+                None => app.load_all_projects(),
+            }
+            app.wait_for_git_status();
+            let format = if json { OutputFormat::Json } else { format };
+            print_projects(&app, format)?;
+        }
+        Command::Open { name, ide } => {
+            app.load_all_projects();
+            let proj = app
+                .find_project(&name)
+                .ok_or_else(|| IdeaError::Project(format!("No project named '{}'", name)))?;
+            if let Some(ide) = ide {
+                app.pending_ide = Some(
+                    app.find_ide(&ide)
+                        .ok_or_else(|| IdeaError::Spawn(format!("Unknown IDE '{}'", ide)))?,
+                );
+            }
+            app.pending_project = Some(proj);
+            app.execute_pending_open()?;
+        }
+        Command::Clone {
+            manifest: Some(path),
+            ..
+        }
+        | Command::Manifest {
+            action: ManifestAction::Import { path },
+        } => clone_manifest(&mut app, &path)?,
+        Command::Manifest {
+            action: ManifestAction::Export { output },
+        } => {
+            let (manifest, skipped) = app.export_manifest();
+            for (path, reason) in skipped {
+                eprintln!("Skipped {}: {}", path.display(), reason);
+            }
+            let toml = manifest.to_toml()?;
+            match output {
+                Some(output) => {
+                    fs::write(&output, toml)?;
+                    eprintln!(
+                        "Wrote {} repositories to {}",
+                        manifest.repos.len(),
+                        output.display()
+                    );
+                }
+                None => print!("{}", toml),
+            }
+        }
+        Command::Clone {
+            url,
+            category,
+            branch,
+            depth,
+            name,
+            recurse_submodules,
+            no_open,
+            ..
+        } => {
+            let (Some(url), Some(category)) = (url, category) else {
+                return Err(IdeaError::Project(
+                    "A URL and --category are required".to_string(),
+                ));
+            };
+            let category = resolve_category(&mut app, &category)?;
+            let default_name = giturl::parse(&url)?.name;
+            app.clone_options = CloneOptions {
+                name: name.unwrap_or(default_name),
+                branch: branch.unwrap_or_default(),
+                depth: depth.map(|d| d.to_string()).unwrap_or_default(),
+                submodules: recurse_submodules,
+                open: !no_open,
+            };
+            app.input = url;
+            let request = app.clone_request(&category)?;
+            clone::run(&request, &AtomicBool::new(false), |progress| {
+                eprint!("\r{:<80}", progress.label());
+            })?;
+            eprintln!();
+            if request.open {
+                app.open_cloned(&request)?;
+            } else {
+                println!("Cloned {} into {}", request.name(), request.target.display());
+            }
+        }
+        Command::Fav { action } => {
+            let (path, favorite) = match action {
+                FavAction::Add { path } => (path, true),
+                FavAction::Rm { path } => (path, false),
+            };
+            // Favorites are stored as absolute paths, so resolve relative input first.
+            let path = path.canonicalize().unwrap_or(path);
+            app.set_favorite(&path, favorite);
+        }
+    }
+
+    if let Some((msg, _)) = app.status_message.take() {
+        println!("{}", msg);
+    }
+    Ok(())
+}
+
+/// Clones the manifest at `path`, printing each repository's outcome as it finishes.
+fn clone_manifest(app: &mut App, path: &Path) -> Result<()> {
+    app.clone_manifest(&Manifest::load(path)?);
+    let mut printed = 0;
+    while let Some(batch) = &app.manifest_batch {
+        for (label, outcome) in &batch.results[printed..] {
+            println!("{:<10} {}", outcome.label(), label);
+        }
+        printed = batch.results.len();
+        if batch.is_finished() {
+            break;
+        }
+        app.wait_for_clone_update();
+    }
+    match app.error.take() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn print_projects(app: &App, format: OutputFormat) -> Result<()> {
+    let records: Vec<ProjectRecord> = app.projects.iter().map(|p| record(app, p)).collect();
+    match format {
+        OutputFormat::Plain => {
+            for r in &records {
+                println!("{:<32} {}", r.name, r.path.display());
+            }
+        }
+        OutputFormat::Json => {
+            let out = serde_json::to_string_pretty(&records)
+                .map_err(|e| IdeaError::Unknown(e.to_string()))?;
+            println!("{}", out);
+        }
+        OutputFormat::Tsv => {
+            for r in &records {
+                println!("{}", tsv_row(r));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tsv_row(r: &ProjectRecord) -> String {
+    [
+        tsv_escape(r.name),
+        tsv_escape(&r.path.to_string_lossy()),
+        tsv_escape(r.git_branch.unwrap_or("")),
+        r.has_changes.to_string(),
+        tsv_escape(r.language.unwrap_or("")),
+        r.favorite.to_string(),
+        r.recent.to_string(),
+    ]
+    .join("\t")
+}
+
+/// Escapes the characters that would split a field or a row.
+fn tsv_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn record<'a>(app: &App, p: &'a ProjectInfo) -> ProjectRecord<'a> {
+    let path_str = p.path.to_str().unwrap_or("").to_string();
+    ProjectRecord {
+        name: &p.name,
+        path: &p.path,
+        git_branch: p.git_branch.as_deref(),
+        has_changes: p.has_changes,
+        language: p.language.as_deref(),
+        favorite: app.is_favorite(&p.path),
+        recent: app.config.recent_projects.contains(&path_str),
+    }
+}
+
+fn resolve_category(app: &mut App, category: &str) -> Result<Category> {
+    app.load_categories();
+    if category == "." {
+        // The first root, matching the old single base_dir behaviour.
+        return app
+            .categories
+            .first()
+            .cloned()
+            .ok_or_else(|| IdeaError::Project("No project roots found".to_string()));
+    }
+    app.find_category(category)
+        .ok_or_else(|| IdeaError::Project(format!("Category '{}' not found", category)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tsv_row_escapes_separators() {
+        let record = ProjectRecord {
+            name: "odd\tname",
+            path: Path::new("/dev/a\\b\nc"),
+            git_branch: Some("main"),
+            has_changes: true,
+            language: None,
+            favorite: false,
+            recent: true,
+        };
+        assert_eq!(
+            tsv_row(&record),
+            "odd\\tname\t/dev/a\\\\b\\nc\tmain\ttrue\t\tfalse\ttrue"
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use crate::error::{IdeaError, Result};
+use crate::giturl;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+/// What to clone and where.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CloneRequest {
+    pub url: String,
+    /// Set for GitHub repositories, which are cloned with `gh repo clone` when gh is
+    /// installed. See `GitUrl::github`.
+    pub github: Option<String>,
+    /// Folder the repository is cloned into. It must not exist yet.
+    pub target: PathBuf,
+    /// Branch to check out instead of the remote's default branch.
+    pub branch: Option<String>,
+    /// Number of commits to fetch for a shallow clone.
+    pub depth: Option<u32>,
+    pub submodules: bool,
+    /// Open the project in its IDE once cloned.
+    pub open: bool,
+}
+
+impl CloneRequest {
+    pub fn name(&self) -> String {
+        self.target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// The clone options form, with text fields kept as typed until the clone starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneOptions {
+    /// Folder name inside the category.
+    pub name: String,
+    /// Empty for the remote's default branch.
+    pub branch: String,
+    /// Empty for the full history.
+    pub depth: String,
+    pub submodules: bool,
+    pub open: bool,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        CloneOptions {
+            name: String::new(),
+            branch: String::new(),
+            depth: String::new(),
+            submodules: false,
+            open: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CloneField {
+    Name,
+    Branch,
+    Depth,
+    Submodules,
+    Open,
+}
+
+impl CloneField {
+    pub const ALL: [CloneField; 5] = [
+        CloneField::Name,
+        CloneField::Branch,
+        CloneField::Depth,
+        CloneField::Submodules,
+        CloneField::Open,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CloneField::Name => "Folder name",
+            CloneField::Branch => "Branch",
+            CloneField::Depth => "Depth",
+            CloneField::Submodules => "Recurse submodules",
+            CloneField::Open => "Open after cloning",
+        }
+    }
+}
+
+impl CloneOptions {
+    /// The editable text of `field`, `None` for the checkboxes.
+    pub fn text_mut(&mut self, field: CloneField) -> Option<&mut String> {
+        match field {
+            CloneField::Name => Some(&mut self.name),
+            CloneField::Branch => Some(&mut self.branch),
+            CloneField::Depth => Some(&mut self.depth),
+            CloneField::Submodules | CloneField::Open => None,
+        }
+    }
+
+    pub fn toggle(&mut self, field: CloneField) {
+        match field {
+            CloneField::Submodules => self.submodules = !self.submodules,
+            CloneField::Open => self.open = !self.open,
+            _ => {}
+        }
+    }
+
+    /// Checks the form and builds the clone of `url`, resolved by `giturl::parse`, into the
+    /// folder `dir`.
+    pub fn request(&self, url: &str, dir: &Path) -> Result<CloneRequest> {
+        let url = giturl::parse(url)?;
+        let name = self.name.trim();
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(IdeaError::Project(format!("Invalid folder name '{}'", name)));
+        }
+        let depth = match self.depth.trim() {
+            "" => None,
+            depth => Some(
+                depth
+                    .parse()
+                    .ok()
+                    .filter(|d| *d > 0)
+                    .ok_or_else(|| {
+                        IdeaError::Project(format!("Depth must be a positive number, not '{}'", depth))
+                    })?,
+            ),
+        };
+        let branch = self.branch.trim();
+        Ok(CloneRequest {
+            url: url.url,
+            github: url.github,
+            target: dir.join(name),
+            branch: (!branch.is_empty()).then(|| branch.to_string()),
+            depth,
+            submodules: self.submodules,
+            open: self.open,
+        })
+    }
+}
+
+/// One line of `git clone --progress` output, e.g.
+/// `Receiving objects:  45% (450/1000), 1.20 MiB | 2.31 MiB/s`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CloneProgress {
+    pub phase: String,
+    pub percent: u8,
+    pub current: u64,
+    pub total: u64,
+    /// Transferred size and speed, only reported while receiving objects.
+    pub throughput: Option<String>,
+}
+
+impl CloneProgress {
+    pub fn label(&self) -> String {
+        let mut label = format!("{} {}% ({}/{})", self.phase, self.percent, self.current, self.total);
+        if let Some(throughput) = &self.throughput {
+            label.push_str(", ");
+            label.push_str(throughput);
+        }
+        label
+    }
+}
+
+pub fn parse_progress(line: &str) -> Option<CloneProgress> {
+    let line = line.trim();
+    let line = line.strip_prefix("remote:").unwrap_or(line).trim();
+    let (phase, rest) = line.split_once(": ")?;
+    let (percent, rest) = rest.trim_start().split_once('%')?;
+    let mut progress = CloneProgress {
+        phase: phase.to_string(),
+        percent: percent.trim().parse().ok()?,
+        ..CloneProgress::default()
+    };
+    if let Some(counts) = rest.trim_start().strip_prefix('(') {
+        let (counts, rest) = counts.split_once(')')?;
+        let (current, total) = counts.split_once('/')?;
+        progress.current = current.parse().ok()?;
+        progress.total = total.parse().ok()?;
+        let rest = rest.trim_start_matches(',').trim();
+        let rest = rest.strip_suffix("done.").unwrap_or(rest);
+        let rest = rest.trim().trim_end_matches(',');
+        if !rest.is_empty() {
+            progress.throughput = Some(rest.to_string());
+        }
+    }
+    Some(progress)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloneState {
+    Running,
+    Done,
+    /// git's error output.
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum CloneEvent {
+    Progress(CloneProgress),
+    Finished(CloneState),
+}
+
+/// Progress of one clone job, produced by its background thread.
+#[derive(Debug)]
+pub struct CloneUpdate {
+    pub id: usize,
+    pub event: CloneEvent,
+}
+
+/// A clone running on a background thread.
+pub struct CloneJob {
+    pub id: usize,
+    pub request: CloneRequest,
+    pub progress: Option<CloneProgress>,
+    pub state: CloneState,
+    cancel: Arc<AtomicBool>,
+}
+
+impl CloneJob {
+    pub fn start(id: usize, request: CloneRequest, tx: Sender<CloneUpdate>) -> CloneJob {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job = CloneJob {
+            id,
+            request: request.clone(),
+            progress: None,
+            state: CloneState::Running,
+            cancel: Arc::clone(&cancel),
+        };
+        thread::spawn(move || {
+            let result = run(&request, &cancel, |progress| {
+                let _ = tx.send(CloneUpdate {
+                    id,
+                    event: CloneEvent::Progress(progress),
+                });
+            });
+            let state = match result {
+                Ok(()) => CloneState::Done,
+                Err(_) if cancel.load(Ordering::Relaxed) => CloneState::Cancelled,
+                Err(IdeaError::Git(message)) => CloneState::Failed(message),
+                Err(e) => CloneState::Failed(e.to_string()),
+            };
+            let _ = tx.send(CloneUpdate {
+                id,
+                event: CloneEvent::Finished(state),
+            });
+        });
+        job
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == CloneState::Running
+    }
+
+    pub fn apply(&mut self, event: CloneEvent) {
+        match event {
+            CloneEvent::Progress(progress) => self.progress = Some(progress),
+            CloneEvent::Finished(state) => self.state = state,
+        }
+    }
+}
+
+/// Clones `request`, passing each progress line to `on_progress`, until the clone exits
+/// or `cancel` is set. GitHub repositories go through `gh repo clone` so gh's login and
+/// protocol settings apply; when gh is missing or fails, `git clone` is tried instead. A
+/// failed or cancelled clone leaves no target folder behind.
+pub fn run(
+    request: &CloneRequest,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(CloneProgress),
+) -> Result<()> {
+    claim_target(&request.target)?;
+    let result = clone_into_target(request, cancel, &mut on_progress);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&request.target);
+    }
+    result
+}
+
+/// Creates the empty target folder git clones into. Creating it is atomic, so of two
+/// clones into the same folder only one gets it, and only that one ever removes it.
+fn claim_target(target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::create_dir(target).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => {
+            IdeaError::Project(format!("{} already exists", target.display()))
+        }
+        _ => IdeaError::Io(e),
+    })
+}
+
+fn clone_into_target(
+    request: &CloneRequest,
+    cancel: &AtomicBool,
+    on_progress: &mut impl FnMut(CloneProgress),
+) -> Result<()> {
+    if let Some(repo) = &request.github {
+        let mut gh = process::Command::new("gh");
+        gh.arg("repo")
+            .arg("clone")
+            .arg(repo)
+            .arg(&request.target)
+            .arg("--")
+            .args(git_flags(request));
+        match watch(gh, request, cancel, on_progress) {
+            Ok(()) => return Ok(()),
+            Err(e) if cancel.load(Ordering::Relaxed) => return Err(e),
+            // git only clones into an empty folder, so drop what gh left behind.
+            Err(_) => {
+                for entry in fs::read_dir(&request.target)?.flatten() {
+                    if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                        fs::remove_dir_all(entry.path())?;
+                    } else {
+                        fs::remove_file(entry.path())?;
+                    }
+                }
+            }
+        }
+    }
+    let mut git = process::Command::new("git");
+    git.arg("clone")
+        .args(git_flags(request))
+        .arg("--")
+        .arg(&request.url)
+        .arg(&request.target);
+    watch(git, request, cancel, on_progress)
+}
+
+/// The `git clone` options for `request`, also passed through by `gh repo clone`.
+fn git_flags(request: &CloneRequest) -> Vec<String> {
+    let mut flags = vec!["--progress".to_string()];
+    if let Some(branch) = &request.branch {
+        flags.push("--branch".to_string());
+        flags.push(branch.clone());
+    }
+    if let Some(depth) = request.depth {
+        flags.push("--depth".to_string());
+        flags.push(depth.to_string());
+    }
+    if request.submodules {
+        flags.push("--recurse-submodules".to_string());
+    }
+    flags
+}
+
+/// Runs one clone command, reporting git's progress from its stderr. Leaves the target
+/// folder for `run` to clean up.
+fn watch(
+    mut command: process::Command,
+    request: &CloneRequest,
+    cancel: &AtomicBool,
+    on_progress: &mut impl FnMut(CloneProgress),
+) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => IdeaError::Git(format!("{} is not installed", program)),
+            _ => IdeaError::Spawn(e.to_string()),
+        })?;
+
+    // Read on a separate thread so the main loop can poll for cancellation.
+    let (line_tx, line_rx) = mpsc::channel();
+    let mut stderr = child.stderr.take();
+    let reader = thread::spawn(move || {
+        let Some(stderr) = stderr.as_mut() else {
+            return;
+        };
+        let mut buf = [0u8; 4096];
+        let mut line = Vec::new();
+        // Progress lines are redrawn in place with `\r`, so split on both line endings.
+        while let Ok(n) = stderr.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            for &byte in &buf[..n] {
+                if byte == b'\r' || byte == b'\n' {
+                    if !line.is_empty() {
+                        let _ = line_tx.send(String::from_utf8_lossy(&line).to_string());
+                        line.clear();
+                    }
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        if !line.is_empty() {
+            let _ = line_tx.send(String::from_utf8_lossy(&line).to_string());
+        }
+    });
+
+    let mut messages = Vec::new();
+    let mut drain = |messages: &mut Vec<String>| {
+        for line in line_rx.try_iter() {
+            match parse_progress(&line) {
+                Some(progress) => on_progress(progress),
+                None => messages.push(line),
+            }
+        }
+    };
+    let status = loop {
+        drain(&mut messages);
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(IdeaError::CloneFailed(format!("{} (cancelled)", request.url)));
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    let _ = reader.join();
+    drain(&mut messages);
+
+    if status.success() {
+        return Ok(());
+    }
+    let errors: Vec<&str> = messages
+        .iter()
+        .map(|m| m.as_str())
+        .filter(|m| m.starts_with("fatal:") || m.starts_with("error:"))
+        .collect();
+    Err(IdeaError::Git(if errors.is_empty() {
+        messages.last().cloned().unwrap_or_else(|| status.to_string())
+    } else {
+        errors.join("\n")
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::init_repo;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_progress_lines() {
+        assert_eq!(
+            parse_progress("Receiving objects:  45% (450/1000), 1.20 MiB | 2.31 MiB/s"),
+            Some(CloneProgress {
+                phase: "Receiving objects".to_string(),
+                percent: 45,
+                current: 450,
+                total: 1000,
+                throughput: Some("1.20 MiB | 2.31 MiB/s".to_string()),
+            })
+        );
+        let done = parse_progress("Resolving deltas: 100% (12/12), done.").unwrap();
+        assert_eq!((done.percent, done.total, done.throughput), (100, 12, None));
+        let remote = parse_progress("remote: Counting objects:   7% (1/14)").unwrap();
+        assert_eq!(remote.phase, "Counting objects");
+        assert_eq!(parse_progress("Cloning into 'api'..."), None);
+        assert_eq!(parse_progress("fatal: repository not found"), None);
+    }
+
+    #[test]
+    fn test_options_build_request() {
+        let mut options = CloneOptions {
+            name: " api ".to_string(),
+            branch: "develop".to_string(),
+            depth: "1".to_string(),
+            ..CloneOptions::default()
+        };
+        options.toggle(CloneField::Submodules);
+        let request = options.request("gh:org/api/", Path::new("/dev")).unwrap();
+        assert_eq!(
+            request,
+            CloneRequest {
+                url: "https://github.com/org/api.git".to_string(),
+                github: Some("org/api".to_string()),
+                target: PathBuf::from("/dev/api"),
+                branch: Some("develop".to_string()),
+                depth: Some(1),
+                submodules: true,
+                open: true,
+            }
+        );
+
+        assert!(options.request("api", Path::new("/dev")).is_err());
+        options.depth = "0".to_string();
+        assert!(options.request("org/api", Path::new("/dev")).is_err());
+        options.depth.clear();
+        options.name = "../api".to_string();
+        assert!(options.request("org/api", Path::new("/dev")).is_err());
+    }
+
+    #[test]
+    fn test_run_clones_and_refuses_existing_target() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source");
+        init_repo(&source, &[]);
+
+        let request = CloneRequest {
+            url: format!("file://{}", source.display()),
+            target: dir.path().join("copy"),
+            ..CloneRequest::default()
+        };
+        let cancel = AtomicBool::new(false);
+        run(&request, &cancel, |_| {}).unwrap();
+        assert!(request.target.join(".git").exists());
+        assert!(matches!(
+            run(&request, &cancel, |_| {}),
+            Err(IdeaError::Project(_))
+        ));
+        // Refusing a folder must not remove it: it belongs to another clone.
+        assert!(request.target.join(".git").exists());
+
+        let missing = CloneRequest {
+            url: format!("file://{}", dir.path().join("missing").display()),
+            target: dir.path().join("nothing"),
+            ..CloneRequest::default()
+        };
+        assert!(matches!(run(&missing, &cancel, |_| {}), Err(IdeaError::Git(_))));
+        assert!(!missing.target.exists());
+    }
+}
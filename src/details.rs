@@ -0,0 +1,178 @@
+use crate::git::GitBackend;
+use glob::Pattern;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const README_LINES: usize = 8;
+const COMMITS: usize = 5;
+
+/// Everything shown in the detail panel for one project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectDetails {
+    pub path: PathBuf,
+    pub readme: Vec<String>,
+    pub commits: Vec<String>,
+    pub remote: Option<String>,
+    pub build_tool: Option<String>,
+    /// Languages by number of source files, most used first.
+    pub languages: Vec<String>,
+}
+
+/// Collects the panel contents. Reads the repository through `git` and walks the project
+/// folder, so call it off the UI thread. Folders matching `ignore` (the configured
+/// `ignore_globs`) are left out of the language count.
+pub fn collect(path: &Path, ignore: &[Pattern], git: &dyn GitBackend) -> ProjectDetails {
+    let mut details = ProjectDetails {
+        path: path.to_path_buf(),
+        readme: readme_excerpt(path),
+        build_tool: build_tool(path),
+        languages: languages(path, ignore),
+        ..ProjectDetails::default()
+    };
+    if path.join(".git").exists() {
+        details.commits = git.recent_commits(path, COMMITS).unwrap_or_default();
+        details.remote = git.remote_url(path).ok().flatten();
+    }
+    details
+}
+
+/// The first lines of prose from the README, skipping badges, HTML and blank lines.
+fn readme_excerpt(path: &Path) -> Vec<String> {
+    let Some(text) = ["README.md", "README", "README.rst", "README.txt", "readme.md"]
+        .iter()
+        .find_map(|f| fs::read_to_string(path.join(f)).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .filter(|l| {
+            !l.is_empty() && !l.starts_with("[!") && !l.starts_with("![") && !l.starts_with('<')
+        })
+        .take(README_LINES)
+        .map(|l| l.to_string())
+        .collect()
+}
+
+fn build_tool(path: &Path) -> Option<String> {
+    let tools = [
+        ("Cargo.toml", "Cargo"),
+        ("pom.xml", "Maven"),
+        ("build.gradle.kts", "Gradle"),
+        ("build.gradle", "Gradle"),
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "Yarn"),
+        ("package.json", "npm"),
+        ("poetry.lock", "Poetry"),
+        ("pyproject.toml", "pip (pyproject)"),
+        ("requirements.txt", "pip"),
+        ("go.mod", "Go modules"),
+        ("CMakeLists.txt", "CMake"),
+        ("Makefile", "Make"),
+    ];
+    tools
+        .iter()
+        .find(|(file, _)| path.join(file).exists())
+        .map(|(_, tool)| tool.to_string())
+}
+
+/// Counts source files by extension up to three folder levels deep, skipping hidden and
+/// ignored folders, and returns the three most common languages.
+fn languages(path: &Path, ignore: &[Pattern]) -> Vec<String> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    count_files(path, 3, ignore, &mut counts);
+    let mut langs: Vec<(&str, usize)> = counts.into_iter().collect();
+    langs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    langs.into_iter().take(3).map(|(l, _)| l.to_string()).collect()
+}
+
+fn count_files(
+    dir: &Path,
+    depth: usize,
+    ignore: &[Pattern],
+    counts: &mut HashMap<&'static str, usize>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && ignore.iter().any(|p| p.matches(name)) => {}
+            Ok(t) if t.is_dir() && depth > 1 => count_files(&path, depth - 1, ignore, counts),
+            Ok(t) if t.is_file() => {
+                if let Some(lang) = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .and_then(language_for)
+                {
+                    *counts.entry(lang).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn language_for(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "rs" => "Rust",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "js" | "jsx" | "mjs" => "JavaScript",
+        "ts" | "tsx" => "TypeScript",
+        "py" => "Python",
+        "go" => "Go",
+        "c" | "h" => "C",
+        "cpp" | "cc" | "hpp" => "C++",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "cs" => "C#",
+        "scala" => "Scala",
+        "sh" => "Shell",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_collect_without_git() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/bin")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::create_dir_all(dir.path().join("generated")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join("build.sh"), "").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/bin/tool.rs"), "").unwrap();
+        fs::write(dir.path().join("target/debug/gen.py"), "").unwrap();
+        fs::write(dir.path().join("generated/a.py"), "").unwrap();
+        fs::write(dir.path().join("generated/b.py"), "").unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "# api\n\n[![CI](badge.svg)](ci)\n<p>logo</p>\nServes the things.\n",
+        )
+        .unwrap();
+
+        let ignore = [Pattern::new("target").unwrap(), Pattern::new("gen*").unwrap()];
+        let details = collect(dir.path(), &ignore, &crate::git::FallbackGit);
+        assert_eq!(details.readme, vec!["api", "Serves the things."]);
+        assert_eq!(details.build_tool.as_deref(), Some("Cargo"));
+        assert_eq!(details.languages, vec!["Rust", "Shell"]);
+        assert!(details.commits.is_empty());
+        assert_eq!(details.remote, None);
+    }
+}
@@ -22,6 +22,9 @@ pub enum IdeaError {
     #[error("Clone failed for: {0}")]
     CloneFailed(String),
 
+    #[error("Manifest error: {0}")]
+    Manifest(String),
+
     #[error("Process spawn error: {0}")]
     Spawn(String),
 
@@ -29,4 +32,18 @@ pub enum IdeaError {
     Unknown(String),
 }
 
+impl From<git2::Error> for IdeaError {
+    fn from(e: git2::Error) -> Self {
+        IdeaError::Git(e.message().to_string())
+    }
+}
+
+impl IdeaError {
+    /// Whether the TUI can keep running after this error. Only a broken terminal ends the
+    /// session; anything else is shown in the error popup.
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(self, IdeaError::Terminal(_))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, IdeaError>;
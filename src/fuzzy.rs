@@ -0,0 +1,131 @@
+/// Score and matched character positions of a fuzzy match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices into the matched text.
+    pub indices: Vec<usize>,
+}
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_CAMEL: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 12;
+const PENALTY_GAP: i64 = 1;
+
+/// Case-insensitive subsequence match. Characters at word boundaries (after `/`, `-`, `_`,
+/// `.`, space, or a camelCase hump) and runs of consecutive characters score higher, while
+/// every skipped character between two matches costs a little.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+    let original: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let (m, n) = (pattern.len(), lower.len());
+    if m > n {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..n)
+        .map(|j| {
+            if j == 0 {
+                return BONUS_BOUNDARY;
+            }
+            let prev = original[j - 1];
+            if matches!(prev, '/' | '-' | '_' | '.' | ' ') {
+                BONUS_BOUNDARY
+            } else if prev.is_lowercase() && original[j].is_uppercase() {
+                BONUS_CAMEL
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    // score[i][j]: best score with pattern[..=i] matched and pattern[i] at text[j].
+    // from[i][j]: where pattern[i - 1] was matched on that best path.
+    let mut score = vec![vec![None::<i64>; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+    for j in 0..n {
+        if lower[j] == pattern[0] {
+            score[0][j] = Some(SCORE_MATCH + bonus[j]);
+        }
+    }
+    for i in 1..m {
+        // Running best of score[i - 1][k] + k * gap over k < j, so a gap of
+        // (j - k - 1) characters can be charged in constant time.
+        let mut best: Option<(i64, usize)> = None;
+        for j in 1..n {
+            if let Some(prev) = score[i - 1][j - 1] {
+                let candidate = prev + (j as i64 - 1) * PENALTY_GAP;
+                if best.is_none_or(|(b, _)| candidate > b) {
+                    best = Some((candidate, j - 1));
+                }
+            }
+            if lower[j] != pattern[i] {
+                continue;
+            }
+            let mut cell: Option<(i64, usize)> = None;
+            if let Some((b, k)) = best {
+                cell = Some((b - (j as i64 - 1) * PENALTY_GAP, k));
+            }
+            if let Some(prev) = score[i - 1][j - 1] {
+                let consecutive = prev + BONUS_CONSECUTIVE;
+                if cell.is_none_or(|(c, _)| consecutive >= c) {
+                    cell = Some((consecutive, j - 1));
+                }
+            }
+            if let Some((c, k)) = cell {
+                score[i][j] = Some(c + SCORE_MATCH + bonus[j]);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (mut j, best) = (0..n)
+        .filter_map(|j| score[m - 1][j].map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+    let mut indices = vec![0; m];
+    for i in (0..m).rev() {
+        indices[i] = j;
+        j = from[i][j];
+    }
+    Some(FuzzyMatch {
+        score: best,
+        indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match_subsequence_and_indices() {
+        assert!(fuzzy_match("xyz", "idea-tui").is_none());
+        let m = fuzzy_match("itu", "idea-tui").unwrap();
+        assert_eq!(m.indices, vec![0, 5, 6]);
+        assert_eq!(fuzzy_match("", "anything").unwrap().indices, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_boundaries_and_runs() {
+        let boundary = fuzzy_match("ts", "team/service").unwrap();
+        let inner = fuzzy_match("ts", "tests").unwrap();
+        assert!(boundary.score > inner.score);
+
+        let run = fuzzy_match("api", "api-gateway").unwrap();
+        let scattered = fuzzy_match("api", "a-pi").unwrap();
+        assert!(run.score > scattered.score);
+
+        let camel = fuzzy_match("ms", "myService").unwrap();
+        assert_eq!(camel.indices, vec![0, 2]);
+    }
+}
@@ -0,0 +1,817 @@
+use crate::error::{IdeaError, Result};
+use crate::models::{Branch, GitOperation, GitStatus};
+use git2::{
+    BranchType, ErrorCode, Repository, RepositoryState, StashFlags, Status, StatusOptions,
+    build::CheckoutBuilder,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, OnceLock},
+};
+
+/// Repository access used by the app. All methods expect `path` to be a working tree root.
+pub trait GitBackend: Send + Sync {
+    /// Branch, upstream and working tree state.
+    fn status(&self, path: &Path) -> Result<GitStatus>;
+    /// Committer date of `HEAD` in Unix seconds, `None` for repositories without commits.
+    fn last_commit(&self, path: &Path) -> Result<Option<u64>>;
+    /// The latest `count` commits from `HEAD` as `<short hash> <summary>` lines, newest first.
+    fn recent_commits(&self, path: &Path, count: usize) -> Result<Vec<String>>;
+    /// Local branches followed by remote-tracking branches, each sorted by name. Symbolic
+    /// refs such as `origin/HEAD` are left out.
+    fn branches(&self, path: &Path) -> Result<Vec<Branch>>;
+    fn checkout(&self, path: &Path, branch: &str) -> Result<()>;
+    /// Creates the local branch `local` tracking the remote-tracking branch `remote` (e.g.
+    /// `origin/feature`) and checks it out.
+    fn track(&self, path: &Path, remote: &str, local: &str) -> Result<()>;
+    /// Creates branch `name` at `HEAD` and checks it out.
+    fn create_branch(&self, path: &Path, name: &str) -> Result<()>;
+    /// Stashes all uncommitted changes, including untracked files.
+    fn stash(&self, path: &Path, message: &str) -> Result<()>;
+    /// URL of the `origin` remote, or of the first remote when there is no `origin`.
+    fn remote_url(&self, path: &Path) -> Result<Option<String>>;
+}
+
+/// The backend the app uses: reads go through libgit2 and fall back to the `git` command
+/// when libgit2 cannot handle a repository. Checkouts prefer the `git` command so hooks
+/// and filters such as Git LFS run, and use libgit2 only when `git` is not installed.
+pub fn backend() -> Arc<dyn GitBackend> {
+    Arc::new(FallbackGit)
+}
+
+pub struct FallbackGit;
+
+impl GitBackend for FallbackGit {
+    fn status(&self, path: &Path) -> Result<GitStatus> {
+        NativeGit.status(path).or_else(|_| CliGit.status(path))
+    }
+
+    fn last_commit(&self, path: &Path) -> Result<Option<u64>> {
+        NativeGit
+            .last_commit(path)
+            .or_else(|_| CliGit.last_commit(path))
+    }
+
+    fn recent_commits(&self, path: &Path, count: usize) -> Result<Vec<String>> {
+        NativeGit
+            .recent_commits(path, count)
+            .or_else(|_| CliGit.recent_commits(path, count))
+    }
+
+    fn branches(&self, path: &Path) -> Result<Vec<Branch>> {
+        NativeGit.branches(path).or_else(|_| CliGit.branches(path))
+    }
+
+    fn checkout(&self, path: &Path, branch: &str) -> Result<()> {
+        Self::writer().checkout(path, branch)
+    }
+
+    fn track(&self, path: &Path, remote: &str, local: &str) -> Result<()> {
+        Self::writer().track(path, remote, local)
+    }
+
+    fn create_branch(&self, path: &Path, name: &str) -> Result<()> {
+        Self::writer().create_branch(path, name)
+    }
+
+    fn stash(&self, path: &Path, message: &str) -> Result<()> {
+        Self::writer().stash(path, message)
+    }
+
+    fn remote_url(&self, path: &Path) -> Result<Option<String>> {
+        NativeGit
+            .remote_url(path)
+            .or_else(|_| CliGit.remote_url(path))
+    }
+}
+
+impl FallbackGit {
+    /// The backend for commands that change the working tree.
+    fn writer() -> &'static dyn GitBackend {
+        if CliGit::available() {
+            &CliGit
+        } else {
+            &NativeGit
+        }
+    }
+}
+
+/// Reads repositories directly through libgit2, without spawning processes.
+pub struct NativeGit;
+
+impl GitBackend for NativeGit {
+    fn status(&self, path: &Path) -> Result<GitStatus> {
+        let repo = Repository::open(path)?;
+        let mut status = GitStatus::default();
+        match repo.head() {
+            Ok(head) if repo.head_detached()? => {
+                status.detached = true;
+                status.head = head.target().map(|oid| oid.to_string()[..7].to_string());
+            }
+            Ok(head) => {
+                status.head = Some(head.shorthand()?.to_string());
+                upstream(&repo, head.name()?, head.target(), &mut status)?;
+            }
+            // A fresh repository: HEAD points at a branch without commits.
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                status.head = repo
+                    .find_reference("HEAD")?
+                    .symbolic_target()?
+                    .map(|t| t.trim_start_matches("refs/heads/").to_string());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .exclude_submodules(true);
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let flags = entry.status();
+            if flags.contains(Status::CONFLICTED) {
+                status.conflicted += 1;
+                continue;
+            }
+            if flags.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            ) {
+                status.staged += 1;
+            }
+            if flags.intersects(
+                Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE,
+            ) {
+                status.unstaged += 1;
+            }
+            if flags.contains(Status::WT_NEW) {
+                status.untracked += 1;
+            }
+        }
+
+        status.operation = match repo.state() {
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(GitOperation::Rebase),
+            RepositoryState::Merge => Some(GitOperation::Merge),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(GitOperation::CherryPick)
+            }
+            RepositoryState::Revert | RepositoryState::RevertSequence => {
+                Some(GitOperation::Revert)
+            }
+            RepositoryState::Bisect => Some(GitOperation::Bisect),
+            _ => None,
+        };
+        Ok(status)
+    }
+
+    fn last_commit(&self, path: &Path) -> Result<Option<u64>> {
+        let repo = Repository::open(path)?;
+        let head = match repo.head() {
+            Ok(head) => head,
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let seconds = head.peel_to_commit()?.committer().when().seconds();
+        Ok(u64::try_from(seconds).ok())
+    }
+
+    fn recent_commits(&self, path: &Path, count: usize) -> Result<Vec<String>> {
+        let repo = Repository::open(path)?;
+        let head = match repo.head() {
+            Ok(head) => head.peel_to_commit()?.id(),
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut walk = repo.revwalk()?;
+        walk.push(head)?;
+        let mut commits = Vec::new();
+        for oid in walk.take(count) {
+            let commit = repo.find_commit(oid?)?;
+            let short = commit.as_object().short_id()?;
+            commits.push(format!(
+                "{} {}",
+                short.as_str()?,
+                commit.summary()?.unwrap_or_default()
+            ));
+        }
+        Ok(commits)
+    }
+
+    fn branches(&self, path: &Path) -> Result<Vec<Branch>> {
+        let repo = Repository::open(path)?;
+        let remotes = repo.remotes()?;
+        let mut remote_names = Vec::new();
+        for name in remotes.iter() {
+            if let Some(name) = name? {
+                remote_names.push(name);
+            }
+        }
+        let mut branches = Vec::new();
+        for branch in repo.branches(None)? {
+            let (branch, kind) = branch?;
+            if branch.get().symbolic_target()?.is_some() {
+                continue;
+            }
+            if let Some(name) = branch.name()? {
+                branches.push(Branch {
+                    name: name.to_string(),
+                    remote: (kind == BranchType::Remote)
+                        .then(|| remote_of(name, &remote_names).to_string()),
+                    current: branch.is_head(),
+                });
+            }
+        }
+        branches.sort_by(|a, b| {
+            (a.is_remote(), &a.name).cmp(&(b.is_remote(), &b.name))
+        });
+        Ok(branches)
+    }
+
+    /// A safe checkout: refuses to overwrite local changes, like `git checkout`.
+    fn checkout(&self, path: &Path, branch: &str) -> Result<()> {
+        let repo = Repository::open(path)?;
+        let (target, reference) = repo.revparse_ext(branch)?;
+        repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+        match reference {
+            Some(reference) => repo.set_head(reference.name()?)?,
+            None => repo.set_head_detached(target.id())?,
+        }
+        Ok(())
+    }
+
+    fn track(&self, path: &Path, remote: &str, local: &str) -> Result<()> {
+        let repo = Repository::open(path)?;
+        let commit = repo
+            .find_branch(remote, BranchType::Remote)?
+            .get()
+            .peel_to_commit()?;
+        repo.branch(local, &commit, false)?
+            .set_upstream(Some(remote))?;
+        self.checkout(path, local)
+    }
+
+    fn create_branch(&self, path: &Path, name: &str) -> Result<()> {
+        let repo = Repository::open(path)?;
+        let head = repo.head()?.peel_to_commit()?;
+        let branch = repo.branch(name, &head, false)?;
+        // The new branch points at the checked out commit, so only HEAD moves.
+        repo.set_head(branch.get().name()?)?;
+        Ok(())
+    }
+
+    fn stash(&self, path: &Path, message: &str) -> Result<()> {
+        let mut repo = Repository::open(path)?;
+        let signature = repo.signature()?;
+        repo.stash_save(&signature, message, Some(StashFlags::INCLUDE_UNTRACKED))?;
+        Ok(())
+    }
+
+    fn remote_url(&self, path: &Path) -> Result<Option<String>> {
+        let repo = Repository::open(path)?;
+        let remotes = repo.remotes()?;
+        let mut names = Vec::new();
+        for name in remotes.iter() {
+            if let Some(name) = name? {
+                names.push(name);
+            }
+        }
+        match preferred_remote(&names) {
+            Some(name) => Ok(Some(repo.find_remote(name)?.url()?.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Fills in the upstream of the local branch `refname`. A configured upstream whose
+/// remote branch is missing is reported as gone.
+fn upstream(
+    repo: &Repository,
+    refname: &str,
+    local: Option<git2::Oid>,
+    status: &mut GitStatus,
+) -> Result<()> {
+    let name = match repo.branch_upstream_name(refname) {
+        Ok(name) => name,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let name = name.as_str()?;
+    status.upstream = Some(
+        name.strip_prefix("refs/remotes/")
+            .or_else(|| name.strip_prefix("refs/heads/"))
+            .unwrap_or(name)
+            .to_string(),
+    );
+    match repo.find_reference(name).ok().and_then(|r| r.target()) {
+        Some(remote) => {
+            if let Some(local) = local {
+                let (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+                status.ahead = ahead as u32;
+                status.behind = behind as u32;
+            }
+        }
+        None => status.upstream_gone = true,
+    }
+    Ok(())
+}
+
+/// Runs the `git` command line tool.
+pub struct CliGit;
+
+impl CliGit {
+    /// Whether the `git` command runs, checked once per session.
+    fn available() -> bool {
+        static AVAILABLE: OnceLock<bool> = OnceLock::new();
+        *AVAILABLE.get_or_init(|| {
+            process::Command::new("git")
+                .arg("--version")
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success())
+        })
+    }
+
+    /// Stdout of a successful git command, otherwise git's own error text.
+    fn run(path: &Path, args: &[&str]) -> Result<String> {
+        let out = process::Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => IdeaError::Git("git is not installed".to_string()),
+                _ => IdeaError::Git(e.to_string()),
+            })?;
+        if out.status.success() {
+            Ok(String::from_utf8_lossy(&out.stdout).to_string())
+        } else {
+            Err(IdeaError::Git(
+                String::from_utf8_lossy(&out.stderr).trim().to_string(),
+            ))
+        }
+    }
+}
+
+impl GitBackend for CliGit {
+    fn status(&self, path: &Path) -> Result<GitStatus> {
+        let output = Self::run(path, &["status", "--porcelain=v2", "--branch"])?;
+        let mut status = parse_status(&output);
+        status.operation = operation(&git_dir(path));
+        Ok(status)
+    }
+
+    fn last_commit(&self, path: &Path) -> Result<Option<u64>> {
+        // `git log` fails on a branch without commits.
+        Ok(Self::run(path, &["log", "-1", "--format=%ct"])
+            .ok()
+            .and_then(|out| out.trim().parse().ok()))
+    }
+
+    fn recent_commits(&self, path: &Path, count: usize) -> Result<Vec<String>> {
+        // `git log` fails on a branch without commits.
+        Ok(Self::run(path, &["log", &format!("-{}", count), "--format=%h %s"])
+            .map(|out| out.lines().map(|l| l.to_string()).collect())
+            .unwrap_or_default())
+    }
+
+    fn branches(&self, path: &Path) -> Result<Vec<Branch>> {
+        let output = Self::run(
+            path,
+            &[
+                "for-each-ref",
+                "--format=%(HEAD)%(symref)|%(refname)",
+                "refs/heads",
+                "refs/remotes",
+            ],
+        )?;
+        let remotes = Self::run(path, &["remote"])?;
+        Ok(parse_branches(&output, &remotes.lines().collect::<Vec<_>>()))
+    }
+
+    fn checkout(&self, path: &Path, branch: &str) -> Result<()> {
+        Self::run(path, &["checkout", branch]).map(|_| ())
+    }
+
+    fn track(&self, path: &Path, remote: &str, local: &str) -> Result<()> {
+        Self::run(path, &["checkout", "-b", local, "--track", remote]).map(|_| ())
+    }
+
+    fn create_branch(&self, path: &Path, name: &str) -> Result<()> {
+        Self::run(path, &["checkout", "-b", name]).map(|_| ())
+    }
+
+    fn stash(&self, path: &Path, message: &str) -> Result<()> {
+        Self::run(path, &["stash", "push", "--include-untracked", "-m", message]).map(|_| ())
+    }
+
+    fn remote_url(&self, path: &Path) -> Result<Option<String>> {
+        let output = Self::run(path, &["remote"])?;
+        let names: Vec<&str> = output.lines().collect();
+        match preferred_remote(&names) {
+            Some(name) => Ok(Some(
+                Self::run(path, &["remote", "get-url", name])?.trim().to_string(),
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+fn preferred_remote<'a>(names: &[&'a str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find(|n| **n == "origin")
+        .or_else(|| names.first())
+        .copied()
+}
+
+/// The remote a remote-tracking branch such as `team/origin/main` belongs to: the longest
+/// of `remotes` it starts with, or its first path segment when none matches.
+fn remote_of<'a>(branch: &'a str, remotes: &[&'a str]) -> &'a str {
+    remotes
+        .iter()
+        .filter(|r| {
+            branch
+                .strip_prefix(**r)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|r| r.len())
+        .copied()
+        .unwrap_or_else(|| branch.split('/').next().unwrap_or(branch))
+}
+
+/// Parses `git for-each-ref --format=%(HEAD)%(symref)|%(refname) refs/heads refs/remotes`,
+/// whose output is already sorted with local branches first. `remotes` are the names of
+/// the configured remotes.
+pub fn parse_branches(output: &str, remotes: &[&str]) -> Vec<Branch> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let current = line.starts_with('*');
+            let (symref, refname) = line.get(1..)?.split_once('|')?;
+            if !symref.is_empty() {
+                return None;
+            }
+            let (name, remote) = match refname.strip_prefix("refs/heads/") {
+                Some(name) => (name, None),
+                None => {
+                    let name = refname.strip_prefix("refs/remotes/")?;
+                    (name, Some(remote_of(name, remotes).to_string()))
+                }
+            };
+            Some(Branch {
+                name: name.to_string(),
+                remote,
+                current,
+            })
+        })
+        .collect()
+}
+
+/// Whether `name` can be used for a new local branch.
+pub fn is_valid_branch_name(name: &str) -> bool {
+    git2::Branch::name_is_valid(name).unwrap_or(false)
+}
+
+/// Parses `git status --porcelain=v2 --branch`.
+pub fn parse_status(porcelain: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut oid = None;
+    let mut has_ab = false;
+    for line in porcelain.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => oid = Some(value),
+                "branch.head" if value == "(detached)" => status.detached = true,
+                "branch.head" => status.head = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    has_ab = true;
+                    let mut counts = value
+                        .split_whitespace()
+                        .map(|n| n.trim_start_matches(['+', '-']).parse().unwrap_or(0));
+                    status.ahead = counts.next().unwrap_or(0);
+                    status.behind = counts.next().unwrap_or(0);
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let mut fields = line.split(' ');
+        match (fields.next(), fields.next()) {
+            (Some("1" | "2"), Some(xy)) => {
+                let mut xy = xy.chars();
+                if xy.next().is_some_and(|x| x != '.') {
+                    status.staged += 1;
+                }
+                if xy.next().is_some_and(|y| y != '.') {
+                    status.unstaged += 1;
+                }
+            }
+            (Some("u"), _) => status.conflicted += 1,
+            (Some("?"), _) => status.untracked += 1,
+            _ => {}
+        }
+    }
+    if status.detached {
+        status.head = oid.map(|o| o.chars().take(7).collect());
+    }
+    // git omits `branch.ab` when the configured upstream branch no longer exists.
+    status.upstream_gone = status.upstream.is_some() && !has_ab;
+    status
+}
+
+/// The repository folder; `.git` is a file pointing elsewhere in worktrees and submodules.
+pub fn git_dir(path: &Path) -> PathBuf {
+    let dot_git = path.join(".git");
+    match fs::read_to_string(&dot_git) {
+        Ok(content) => content
+            .trim()
+            .strip_prefix("gitdir: ")
+            .map(|dir| path.join(dir))
+            .unwrap_or(dot_git),
+        Err(_) => dot_git,
+    }
+}
+
+/// The folder shared by all worktrees of a repository, which holds `FETCH_HEAD`. Linked
+/// worktrees name it in their `commondir` file.
+pub fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(content) => git_dir.join(content.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+fn operation(git_dir: &Path) -> Option<GitOperation> {
+    let markers = [
+        ("rebase-merge", GitOperation::Rebase),
+        ("rebase-apply", GitOperation::Rebase),
+        ("MERGE_HEAD", GitOperation::Merge),
+        ("CHERRY_PICK_HEAD", GitOperation::CherryPick),
+        ("REVERT_HEAD", GitOperation::Revert),
+        ("BISECT_LOG", GitOperation::Bisect),
+    ];
+    markers
+        .into_iter()
+        .find(|(marker, _)| git_dir.join(marker).exists())
+        .map(|(_, op)| op)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// A repository at `path` whose one commit, `init`, adds `files`. The committer is also
+    /// set in the repository config, for operations that write commits of their own.
+    pub(crate) fn init_repo(path: &Path, files: &[(&str, &str)]) -> Repository {
+        let repo = Repository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "t").unwrap();
+        config.set_str("user.email", "t@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        for (name, contents) in files {
+            fs::write(path.join(name), contents).unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        let sig = git2::Signature::new("t", "t@example.com", &git2::Time::new(1_700_000_000, 0))
+            .unwrap();
+        {
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                .unwrap();
+        }
+        repo
+    }
+
+    #[test]
+    fn test_parse_status_counts_and_upstream() {
+        let status = parse_status(
+            "# branch.oid 1a2b3c4d5e6f\n\
+             # branch.head main\n\
+             # branch.upstream origin/main\n\
+             # branch.ab +2 -1\n\
+             1 M. N... 100644 100644 100644 a b src/lib.rs\n\
+             1 .M N... 100644 100644 100644 a b README.md\n\
+             1 MM N... 100644 100644 100644 a b Cargo.toml\n\
+             2 R. N... 100644 100644 100644 a b R100 new.rs\told.rs\n\
+             u UU N... 100644 100644 100644 100644 a b c conflict.rs\n\
+             ? notes.txt\n",
+        );
+        assert_eq!(
+            status,
+            GitStatus {
+                head: Some("main".to_string()),
+                upstream: Some("origin/main".to_string()),
+                ahead: 2,
+                behind: 1,
+                staged: 3,
+                unstaged: 2,
+                untracked: 1,
+                conflicted: 1,
+                ..GitStatus::default()
+            }
+        );
+        assert!(status.is_dirty());
+    }
+
+    #[test]
+    fn test_parse_status_detached_and_gone() {
+        let detached = parse_status("# branch.oid 1a2b3c4d5e6f\n# branch.head (detached)\n");
+        assert!(detached.detached);
+        assert_eq!(detached.head.as_deref(), Some("1a2b3c4"));
+        assert!(!detached.is_dirty());
+
+        let gone = parse_status(
+            "# branch.oid 1a2b3c4\n# branch.head topic\n# branch.upstream origin/topic\n",
+        );
+        assert!(gone.upstream_gone);
+    }
+
+    #[test]
+    fn test_operation_in_progress() {
+        let dir = tempdir().unwrap();
+        assert_eq!(operation(dir.path()), None);
+        fs::create_dir(dir.path().join("rebase-merge")).unwrap();
+        assert_eq!(operation(dir.path()), Some(GitOperation::Rebase));
+    }
+
+    #[test]
+    fn test_native_status_matches_cli() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo(dir.path(), &[("a.txt", "one")]);
+        let mut index = repo.index().unwrap();
+
+        fs::write(dir.path().join("a.txt"), "two").unwrap();
+        fs::write(dir.path().join("b.txt"), "new").unwrap();
+        index.add_path(Path::new("b.txt")).unwrap();
+        index.write().unwrap();
+        fs::write(dir.path().join("c.txt"), "untracked").unwrap();
+
+        let native = NativeGit.status(dir.path()).unwrap();
+        assert_eq!((native.staged, native.unstaged, native.untracked), (1, 1, 1));
+        assert!(native.head.is_some());
+        assert_eq!(NativeGit.last_commit(dir.path()).unwrap(), Some(1_700_000_000));
+        assert_eq!(NativeGit.branches(dir.path()).unwrap().len(), 1);
+        if CliGit::available() {
+            assert_eq!(CliGit.status(dir.path()).unwrap(), native);
+        }
+    }
+
+    #[test]
+    fn test_parse_branches_groups_local_and_remote() {
+        let branches = parse_branches(concat!(
+            " |refs/heads/feature\n",
+            "*|refs/heads/main\n",
+            " refs/remotes/origin/main|refs/remotes/origin/HEAD\n",
+            " |refs/remotes/origin/release/1.0\n",
+        ), &["origin"]);
+        let names: Vec<(&str, bool, bool)> = branches
+            .iter()
+            .map(|b| (b.name.as_str(), b.is_remote(), b.current))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("feature", false, false),
+                ("main", false, true),
+                ("origin/release/1.0", true, false),
+            ]
+        );
+        assert_eq!(branches[2].local_name(), "release/1.0");
+    }
+
+    #[test]
+    fn test_native_track_and_create_branch() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo(dir.path(), &[]);
+        let oid = repo.head().unwrap().target().unwrap();
+        repo.remote("origin", "https://example.com/tool.git").unwrap();
+        repo.reference("refs/remotes/origin/feature", oid, false, "test")
+            .unwrap();
+
+        let branches = NativeGit.branches(dir.path()).unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(branches[0].current && !branches[0].is_remote());
+        assert_eq!(branches[1].name, "origin/feature");
+        assert_eq!(branches[1].remote.as_deref(), Some("origin"));
+
+        NativeGit.track(dir.path(), "origin/feature", "feature").unwrap();
+        let status = NativeGit.status(dir.path()).unwrap();
+        assert_eq!(status.head.as_deref(), Some("feature"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/feature"));
+
+        NativeGit.create_branch(dir.path(), "topic").unwrap();
+        let status = NativeGit.status(dir.path()).unwrap();
+        assert_eq!(status.head.as_deref(), Some("topic"));
+        assert_eq!(status.upstream, None);
+        if CliGit::available() {
+            assert_eq!(
+                CliGit.branches(dir.path()).unwrap(),
+                NativeGit.branches(dir.path()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_track_remote_whose_name_contains_a_slash() {
+        let mut backends: Vec<&dyn GitBackend> = vec![&NativeGit];
+        if CliGit::available() {
+            backends.push(&CliGit);
+        }
+        for backend in backends {
+            let dir = tempdir().unwrap();
+            let repo = init_repo(dir.path(), &[]);
+            let oid = repo.head().unwrap().target().unwrap();
+            repo.remote("team/origin", "https://example.com/tool.git").unwrap();
+            repo.reference("refs/remotes/team/origin/feature/x", oid, false, "test")
+                .unwrap();
+
+            let branches = backend.branches(dir.path()).unwrap();
+            let remote = branches.iter().find(|b| b.is_remote()).unwrap();
+            assert_eq!(remote.remote.as_deref(), Some("team/origin"));
+            assert_eq!(remote.local_name(), "feature/x");
+
+            backend
+                .track(dir.path(), &remote.name, remote.local_name())
+                .unwrap();
+            let status = NativeGit.status(dir.path()).unwrap();
+            assert_eq!(status.head.as_deref(), Some("feature/x"));
+            assert_eq!(status.upstream.as_deref(), Some("team/origin/feature/x"));
+        }
+    }
+
+    #[test]
+    fn test_recent_commits_newest_first() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(NativeGit.recent_commits(dir.path(), 5).unwrap().is_empty());
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("t", "t@example.com").unwrap();
+        let first = repo
+            .commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])
+            .unwrap();
+        let parent = repo.find_commit(first).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "second\n\nbody", &tree, &[&parent])
+            .unwrap();
+
+        let commits = NativeGit.recent_commits(dir.path(), 5).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert!(commits[0].ends_with(" second") && commits[1].ends_with(" first"));
+        assert_eq!(NativeGit.recent_commits(dir.path(), 1).unwrap().len(), 1);
+        if CliGit::available() {
+            assert_eq!(CliGit.recent_commits(dir.path(), 5).unwrap(), commits);
+        }
+    }
+
+    #[test]
+    fn test_remote_url_prefers_origin() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert_eq!(NativeGit.remote_url(dir.path()).unwrap(), None);
+        repo.remote("fork", "git@host:me/tool.git").unwrap();
+        assert_eq!(
+            NativeGit.remote_url(dir.path()).unwrap().as_deref(),
+            Some("git@host:me/tool.git")
+        );
+        repo.remote("origin", "https://example.com/tool.git").unwrap();
+        assert_eq!(
+            NativeGit.remote_url(dir.path()).unwrap().as_deref(),
+            Some("https://example.com/tool.git")
+        );
+        if CliGit::available() {
+            assert_eq!(
+                CliGit.remote_url(dir.path()).unwrap(),
+                NativeGit.remote_url(dir.path()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_native_stash_includes_untracked() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path(), &[("a.txt", "one")]);
+
+        fs::write(dir.path().join("a.txt"), "two").unwrap();
+        fs::write(dir.path().join("b.txt"), "new").unwrap();
+        assert!(NativeGit.status(dir.path()).unwrap().is_dirty());
+        NativeGit.stash(dir.path(), "switching").unwrap();
+        assert!(!NativeGit.status(dir.path()).unwrap().is_dirty());
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "one");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::error::{IdeaError, Result};
+
+/// A repository address typed by the user, resolved to a URL `git clone` understands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitUrl {
+    pub url: String,
+    /// Repository name without `.git`, used as the default folder name.
+    pub name: String,
+    /// What to hand `gh repo clone` for GitHub repositories: `owner/repo` for the
+    /// shorthand, so gh's protocol setting applies, or the URL as typed.
+    pub github: Option<String>,
+}
+
+const SCHEMES: [&str; 6] = ["https://", "http://", "ssh://", "git://", "git+ssh://", "file://"];
+
+/// Accepts `owner/repo` and `gh:owner/repo` (both GitHub), scp-style SSH such as
+/// `git@host:org/repo.git`, and `https://`, `http://`, `ssh://`, `git://` and `file://`
+/// URLs. Query strings, fragments and trailing slashes are dropped.
+pub fn parse(input: &str) -> Result<GitUrl> {
+    let input = input.trim();
+    let invalid = |reason: &str| IdeaError::Project(format!("'{}' {}", input, reason));
+    if input.is_empty() {
+        return Err(IdeaError::Project("Enter a repository URL".to_string()));
+    }
+    if input.chars().any(char::is_whitespace) {
+        return Err(invalid("contains spaces"));
+    }
+
+    let (url, path, github) = if let Some(scheme) = SCHEMES.iter().find(|s| input.starts_with(*s)) {
+        let rest = &input[scheme.len()..];
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let rest = rest.trim_end_matches('/');
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if host.is_empty() && *scheme != "file://" {
+            return Err(invalid("has no host"));
+        }
+        let url = format!("{}{}", scheme, rest);
+        let github = is_github(host.split(':').next().unwrap_or_default()).then(|| url.clone());
+        (url, path.to_string(), github)
+    } else if input.contains("://") {
+        return Err(invalid("uses an unsupported protocol"));
+    } else if let Some((owner, repo)) = github_shorthand(input) {
+        (
+            format!("https://github.com/{}/{}.git", owner, repo),
+            repo.to_string(),
+            Some(format!("{}/{}", owner, repo)),
+        )
+    } else if input.starts_with("gh:") {
+        return Err(invalid("is not a GitHub owner/repo"));
+    } else if let Some((host, path)) = input.split_once(':')
+        && !host.is_empty()
+        && !host.contains('/')
+    {
+        // scp-style `[user@]host:path`
+        let path = path.trim_end_matches('/');
+        let url = format!("{}:{}", host, path);
+        let github = is_github(host).then(|| url.clone());
+        (url, path.to_string(), github)
+    } else {
+        return Err(invalid("is not a git URL or owner/repo"));
+    };
+
+    let name = path
+        .rsplit('/')
+        .next()
+        .map(|n| n.strip_suffix(".git").unwrap_or(n))
+        .unwrap_or_default();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(invalid("has no repository name"));
+    }
+    Ok(GitUrl {
+        url,
+        name: name.to_string(),
+        github,
+    })
+}
+
+/// Whether `host`, optionally with a `user@` prefix, is github.com.
+fn is_github(host: &str) -> bool {
+    let host = host.rsplit('@').next().unwrap_or(host);
+    host.eq_ignore_ascii_case("github.com") || host.eq_ignore_ascii_case("www.github.com")
+}
+
+/// `owner/repo` or `gh:owner/repo`.
+fn github_shorthand(input: &str) -> Option<(&str, &str)> {
+    let input = input.strip_prefix("gh:").unwrap_or(input);
+    let (owner, repo) = input.trim_end_matches('/').split_once('/')?;
+    let valid = |s: &str| {
+        !s.is_empty()
+            && s != "."
+            && s != ".."
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    (valid(owner) && valid(repo)).then(|| (owner, repo.strip_suffix(".git").unwrap_or(repo)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(input: &str) -> (String, String) {
+        let url = parse(input).unwrap();
+        (url.url, url.name)
+    }
+
+    fn github(input: &str) -> Option<String> {
+        parse(input).unwrap().github
+    }
+
+    #[test]
+    fn test_parse_accepted_forms() {
+        let github = (
+            "https://github.com/rust-lang/cargo.git".to_string(),
+            "cargo".to_string(),
+        );
+        assert_eq!(resolved("rust-lang/cargo"), github);
+        assert_eq!(resolved("gh:rust-lang/cargo.git"), github);
+        assert_eq!(
+            resolved("git@gitlab.com:group/sub/api.git"),
+            ("git@gitlab.com:group/sub/api.git".to_string(), "api".to_string())
+        );
+        assert_eq!(
+            resolved(" https://example.com/org/tool/?tab=readme#top "),
+            ("https://example.com/org/tool".to_string(), "tool".to_string())
+        );
+        assert_eq!(
+            resolved("ssh://git@host:2222/org/svc.git"),
+            ("ssh://git@host:2222/org/svc.git".to_string(), "svc".to_string())
+        );
+        assert_eq!(
+            resolved("file:///srv/git/lib.git/"),
+            ("file:///srv/git/lib.git".to_string(), "lib".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_marks_github_repositories() {
+        assert_eq!(github("gh:rust-lang/cargo.git"), Some("rust-lang/cargo".to_string()));
+        assert_eq!(
+            github("git@github.com:rust-lang/cargo.git"),
+            Some("git@github.com:rust-lang/cargo.git".to_string())
+        );
+        assert_eq!(
+            github("https://github.com/rust-lang/cargo"),
+            Some("https://github.com/rust-lang/cargo".to_string())
+        );
+        assert_eq!(github("git@gitlab.com:group/api.git"), None);
+        assert_eq!(github("https://example.com/org/tool"), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        for input in [
+            "",
+            "just-a-name",
+            "https://",
+            "https://example.com/",
+            "ftp://example.com/repo.git",
+            "git@host:",
+            "owner/repo name",
+            "/local/path",
+            "../repo",
+            "./repo",
+            "owner/..",
+            "owner/.",
+            "gh:../repo",
+        ] {
+            assert!(parse(input).is_err(), "{:?} should be rejected", input);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::models::{IdeLauncher, IdeRecent};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Launcher names shipped in JetBrains `bin` folders, with their display names.
+const PRODUCTS: [(&str, &str); 10] = [
+    ("idea", "IntelliJ IDEA"),
+    ("rustrover", "RustRover"),
+    ("pycharm", "PyCharm"),
+    ("goland", "GoLand"),
+    ("webstorm", "WebStorm"),
+    ("clion", "CLion"),
+    ("phpstorm", "PhpStorm"),
+    ("rider", "Rider"),
+    ("datagrip", "DataGrip"),
+    ("rubymine", "RubyMine"),
+];
+
+/// How a search root is laid out.
+#[derive(Clone, Copy)]
+pub enum ScanKind {
+    /// Install folders containing `.../bin/<launcher>`, searched a few levels deep.
+    Tree,
+    /// A flat folder of launcher scripts or exported binaries.
+    Flat,
+}
+
+/// The usual Linux install locations for JetBrains IDEs.
+pub fn default_roots() -> Vec<(PathBuf, ScanKind, &'static str)> {
+    let data = dirs::data_dir().unwrap_or_else(|| PathBuf::from("/"));
+    vec![
+        (PathBuf::from("/opt"), ScanKind::Tree, "opt"),
+        (data.join("JetBrains/Toolbox/apps"), ScanKind::Tree, "toolbox"),
+        (data.join("JetBrains/Toolbox/scripts"), ScanKind::Flat, "toolbox"),
+        (PathBuf::from("/snap/bin"), ScanKind::Flat, "snap"),
+        (PathBuf::from("/var/lib/flatpak/exports/bin"), ScanKind::Flat, "flatpak"),
+        (data.join("flatpak/exports/bin"), ScanKind::Flat, "flatpak"),
+    ]
+}
+
+pub fn discover_ides() -> Vec<IdeLauncher> {
+    discover_in(&default_roots())
+}
+
+/// Scans `roots` in order. The first launcher of a product keeps the plain product name,
+/// later ones are suffixed with the source they came from.
+pub fn discover_in(roots: &[(PathBuf, ScanKind, &str)]) -> Vec<IdeLauncher> {
+    let mut found: Vec<(&str, PathBuf, &str)> = Vec::new();
+    for (root, kind, source) in roots {
+        let mut launchers = Vec::new();
+        match kind {
+            ScanKind::Tree => scan_tree(root, 4, &mut launchers),
+            ScanKind::Flat => scan_flat(root, &mut launchers),
+        }
+        found.extend(launchers.into_iter().map(|(name, path)| (name, path, *source)));
+    }
+
+    let mut ides: Vec<IdeLauncher> = Vec::new();
+    for (product, path, source) in found {
+        let path_str = path.to_string_lossy().to_string();
+        if ides.iter().any(|i| i.path == path_str) {
+            continue;
+        }
+        let mut name = product.to_string();
+        if ides.iter().any(|i| i.name == name) {
+            name = format!("{} ({})", product, source);
+        }
+        if ides.iter().any(|i| i.name == name) {
+            continue;
+        }
+        ides.push(IdeLauncher {
+            name,
+            path: path_str,
+        });
+    }
+    ides
+}
+
+fn scan_tree(dir: &Path, depth: usize, found: &mut Vec<(&'static str, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    for sub in dirs {
+        if sub.file_name().and_then(|n| n.to_str()) == Some("bin") {
+            scan_bin(&sub, found);
+        } else if depth > 0 {
+            scan_tree(&sub, depth - 1, found);
+        }
+    }
+}
+
+/// Looks for `<launcher>` or `<launcher>.sh`, preferring the native launcher.
+fn scan_bin(bin: &Path, found: &mut Vec<(&'static str, PathBuf)>) {
+    for (launcher, name) in PRODUCTS {
+        let candidates = [bin.join(launcher), bin.join(format!("{}.sh", launcher))];
+        if let Some(path) = candidates.into_iter().find(|p| p.is_file()) {
+            found.push((name, path));
+        }
+    }
+}
+
+fn scan_flat(dir: &Path, found: &mut Vec<(&'static str, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    files.sort();
+    for path in files {
+        if let Some(name) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(product_for)
+        {
+            found.push((name, path));
+        }
+    }
+}
+
+/// Maps script names like `pycharm`, `intellij-idea-ultimate` or
+/// `com.jetbrains.GoLand` to a product display name.
+fn product_for(file_name: &str) -> Option<&'static str> {
+    let lower = file_name.to_lowercase();
+    let stem = lower.strip_prefix("com.jetbrains.").unwrap_or(&lower);
+    let stem = stem.strip_prefix("intellij-").unwrap_or(stem);
+    PRODUCTS
+        .iter()
+        .find(|(launcher, _)| stem.starts_with(launcher))
+        .map(|(_, name)| *name)
+}
+
+/// Reads `recentProjects.xml` of every JetBrains product under `~/.config/JetBrains`,
+/// newest first. A project listed by several products keeps its latest timestamp.
+pub fn jetbrains_recent_projects() -> Vec<IdeRecent> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+    let mut merged: Vec<IdeRecent> = Vec::new();
+    let Ok(products) = fs::read_dir(config.join("JetBrains")) else {
+        return merged;
+    };
+    for product in products.flatten() {
+        let file = product.path().join("options/recentProjects.xml");
+        let Ok(xml) = fs::read_to_string(&file) else {
+            continue;
+        };
+        for recent in parse_recent_projects(&xml, &home) {
+            match merged.iter_mut().find(|r| r.path == recent.path) {
+                Some(existing) if existing.timestamp < recent.timestamp => *existing = recent,
+                Some(_) => {}
+                None => merged.push(recent),
+            }
+        }
+    }
+    merged.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+    merged
+}
+
+/// Parses the `RecentProjectsManager` component. Newer IDEs keep an `additionalInfo` map with
+/// frame titles and timestamps; older ones only a `recentPaths` list.
+pub fn parse_recent_projects(xml: &str, home: &Path) -> Vec<IdeRecent> {
+    let Ok(doc) = roxmltree::Document::parse(xml) else {
+        return Vec::new();
+    };
+    let expand = |p: &str| PathBuf::from(p.replace("$USER_HOME$", &home.to_string_lossy()));
+
+    let mut recent = Vec::new();
+    if let Some(info) = option(doc.root(), "additionalInfo") {
+        for entry in info.descendants().filter(|n| n.has_tag_name("entry")) {
+            let Some(key) = entry.attribute("key") else {
+                continue;
+            };
+            let meta = entry
+                .descendants()
+                .find(|n| n.has_tag_name("RecentProjectMetaInfo"));
+            let timestamp = meta.and_then(|m| {
+                ["activationTimestamp", "projectOpenTimestamp"]
+                    .iter()
+                    .filter_map(|name| option(m, name)?.attribute("value")?.parse::<u64>().ok())
+                    .next()
+                    // The IDEs store milliseconds.
+                    .map(|ms| ms / 1000)
+            });
+            recent.push(IdeRecent {
+                path: expand(key),
+                frame_title: meta
+                    .and_then(|m| m.attribute("frameTitle"))
+                    .map(|t| t.to_string()),
+                timestamp,
+            });
+        }
+    } else if let Some(paths) = option(doc.root(), "recentPaths") {
+        for value in paths
+            .descendants()
+            .filter(|n| n.has_tag_name("option"))
+            .filter_map(|n| n.attribute("value"))
+        {
+            recent.push(IdeRecent {
+                path: expand(value),
+                frame_title: None,
+                timestamp: None,
+            });
+        }
+    }
+    recent
+}
+
+fn option<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.descendants()
+        .find(|n| n.has_tag_name("option") && n.attribute("name") == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_discover_in_tree_and_flat_roots() {
+        let dir = tempdir().unwrap();
+        let opt = dir.path().join("opt");
+        fs::create_dir_all(opt.join("pycharm-2024.1/bin")).unwrap();
+        fs::write(opt.join("pycharm-2024.1/bin/pycharm.sh"), "").unwrap();
+        fs::write(opt.join("pycharm-2024.1/bin/format.sh"), "").unwrap();
+        let snap = dir.path().join("snap");
+        fs::create_dir_all(&snap).unwrap();
+        fs::write(snap.join("intellij-idea-ultimate"), "").unwrap();
+        fs::write(snap.join("pycharm-community"), "").unwrap();
+        fs::write(snap.join("firefox"), "").unwrap();
+
+        let ides = discover_in(&[
+            (opt.clone(), ScanKind::Tree, "opt"),
+            (snap.clone(), ScanKind::Flat, "snap"),
+        ]);
+        let names: Vec<&str> = ides.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["PyCharm", "IntelliJ IDEA", "PyCharm (snap)"]);
+        assert_eq!(
+            ides[0].path,
+            opt.join("pycharm-2024.1/bin/pycharm.sh").to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_parse_recent_projects_additional_info() {
+        let xml = r#"<application>
+  <component name="RecentProjectsManager">
+    <option name="additionalInfo">
+      <map>
+        <entry key="$USER_HOME$/dev/api">
+          <value>
+            <RecentProjectMetaInfo frameTitle="api – main.rs">
+              <option name="activationTimestamp" value="1700000000000" />
+              <option name="projectOpenTimestamp" value="1600000000000" />
+            </RecentProjectMetaInfo>
+          </value>
+        </entry>
+        <entry key="/srv/tool">
+          <value>
+            <RecentProjectMetaInfo />
+          </value>
+        </entry>
+      </map>
+    </option>
+  </component>
+</application>"#;
+        let recent = parse_recent_projects(xml, Path::new("/home/me"));
+        assert_eq!(
+            recent,
+            vec![
+                IdeRecent {
+                    path: PathBuf::from("/home/me/dev/api"),
+                    frame_title: Some("api – main.rs".to_string()),
+                    timestamp: Some(1700000000),
+                },
+                IdeRecent {
+                    path: PathBuf::from("/srv/tool"),
+                    frame_title: None,
+                    timestamp: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_recent_projects_recent_paths() {
+        let xml = r#"<application>
+  <component name="RecentProjectsManager">
+    <option name="recentPaths">
+      <list>
+        <option value="$USER_HOME$/dev/old" />
+      </list>
+    </option>
+  </component>
+</application>"#;
+        let recent = parse_recent_projects(xml, Path::new("/home/me"));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, PathBuf::from("/home/me/dev/old"));
+    }
+}
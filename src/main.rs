@@ -1,14 +1,23 @@
 mod app;
+mod cache;
+mod cli;
+mod clone;
+mod details;
 mod error;
+mod fuzzy;
+mod git;
+mod giturl;
+mod ide;
+mod manifest;
 mod models;
+mod query;
+mod scan;
+mod terminal;
 mod theme;
 mod ui;
 
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{
     Terminal,
     backend::{Backend, CrosstermBackend},
@@ -16,30 +25,35 @@ use ratatui::{
 use std::{io, process, time::Duration};
 
 use crate::app::App;
+use crate::cli::Cli;
 use crate::error::{IdeaError, Result};
 use crate::models::{AppMode, Config};
+use crate::terminal::{TerminalGuard, install_panic_hook};
 use crate::ui::ui;
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
     let cfg: Config = confy::load("idea-tui", None)?;
-    enable_raw_mode().map_err(IdeaError::Io)?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture).map_err(IdeaError::Io)?;
-    let backend = CrosstermBackend::new(stdout);
+    if let Some(command) = cli.command {
+        if let Err(err) = cli::run(command, cfg) {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    install_panic_hook();
+    let guard = TerminalGuard::new()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).map_err(IdeaError::Io)?;
     let mut app = App::new(cfg);
+    // Missing roots are fixed first; the IDE setup follows once one is added.
+    if app.mode == AppMode::MainMenu && app.needs_ide_setup() {
+        app.start_ide_setup();
+    }
     let res = run_app(&mut terminal, &mut app);
-    disable_raw_mode().map_err(IdeaError::Io)?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    ).map_err(IdeaError::Io)?;
-    terminal
-        .show_cursor()
-        .map_err(IdeaError::Io)?;
+    drop(guard);
     if let Err(err) = res {
-        println!("{:?}", err);
+        eprintln!("Error: {}", err);
         process::exit(1);
     }
     Ok(())
@@ -47,6 +61,9 @@ fn main() -> Result<()> {
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
     loop {
+        app.poll_git_updates();
+        app.poll_details();
+        app.poll_clones();
         if let Some((_, time)) = app.status_message && time.elapsed() > Duration::from_secs(3) {
             app.status_message = None;
         }
@@ -56,162 +73,268 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
         if event::poll(Duration::from_millis(100))?
             && let Event::Key(key) = event::read()?
         {
-            if app.mode == AppMode::ConfirmOpen {
-                match key.code {
-                    KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
-                        app.execute_pending_open()?;
-                    }
-                    KeyCode::Char('n')
-                    | KeyCode::Char('N')
-                    | KeyCode::Esc
-                    | KeyCode::Backspace => {
-                        app.go_back();
-                    }
-                    _ => {}
+            if app.error.is_some() {
+                app.error = None;
+            } else {
+                match handle_key(app, key) {
+                    Ok(true) => return Ok(()),
+                    Ok(false) => {}
+                    // Failed actions are shown in the error popup instead of ending the session.
+                    Err(e) if e.is_recoverable() => app.error = Some(e),
+                    Err(e) => return Err(e),
                 }
-            } else if app.mode == AppMode::Help {
+            }
+        }
+    }
+}
+
+/// Handles one key press. Returns `true` when the app should quit.
+fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
+    if app.confirm_quit {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                app.cancel_all_clones();
+                return Ok(true);
+            }
+            KeyCode::Char('n')
+            | KeyCode::Char('N')
+            | KeyCode::Esc
+            | KeyCode::Backspace => app.confirm_quit = false,
+            _ => {}
+        }
+    } else if app.mode == AppMode::ConfirmOpen {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                app.execute_pending_open()?;
+            }
+            KeyCode::Char('o') => app.open_with(),
+            KeyCode::Char('n')
+            | KeyCode::Char('N')
+            | KeyCode::Esc
+            | KeyCode::Backspace => {
                 app.go_back();
-            } else if app.mode == AppMode::BranchSelection {
-                match key.code {
-                    KeyCode::Enter => {
-                        app.on_enter()?;
-                    }
-                    KeyCode::Char('c') => {
-                        app.checkout_only()?;
-                    }
-                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
-                        app.go_back();
-                    }
-                    KeyCode::Down | KeyCode::Char('j') => app.next(),
-                    KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                    _ => {}
-                }
-            } else if app.is_searching {
-                match key.code {
-                    KeyCode::Enter => {
-                        app.is_searching = false;
-                    }
-                    KeyCode::Char(c) => {
-                        app.search_query.push(c);
-                        if let AppMode::CategorySelection | AppMode::CloneCategory = app.mode {
-                            app.category_state.select(Some(0));
-                        } else {
-                            app.project_state.select(Some(0));
-                        }
-                    }
-                    KeyCode::Backspace => {
-                        app.search_query.pop();
-                    }
-                    KeyCode::Esc => {
-                        app.is_searching = false;
-                        app.search_query.clear();
-                    }
-                    _ => {}
-                }
-            } else if app.mode == AppMode::InputUrl {
-                match key.code {
-                    KeyCode::Enter => {
-                        app.on_enter()?;
-                    }
-                    KeyCode::Char(c) => {
-                        app.input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        if app.input.is_empty() {
-                            app.mode = AppMode::MainMenu;
-                        } else {
-                            app.input.pop();
-                        }
-                    }
-                    KeyCode::Esc => {
-                        app.mode = AppMode::MainMenu;
-                    }
-                    _ => {}
+            }
+            _ => {}
+        }
+    } else if app.mode == AppMode::OpenWith {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
+                app.go_back();
+            }
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            _ => {}
+        }
+    } else if app.mode == AppMode::IdeSetup {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Char(' ') => app.toggle_ide_checked(),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
+                app.go_back();
+            }
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            _ => {}
+        }
+    } else if app.mode == AppMode::Help {
+        app.go_back();
+    } else if app.mode == AppMode::BranchSelection {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Char('c') => app.start_checkout(false),
+            KeyCode::Char('n') => {
+                app.input.clear();
+                app.mode = AppMode::NewBranch;
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
+                app.go_back();
+            }
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            _ => {}
+        }
+    } else if app.mode == AppMode::CloneOptions {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Down | KeyCode::Tab => app.next(),
+            KeyCode::Up | KeyCode::BackTab => app.previous(),
+            KeyCode::Char(c) => app.clone_option_input(c),
+            KeyCode::Backspace => app.clone_option_backspace(),
+            KeyCode::Esc => app.go_back(),
+            _ => {}
+        }
+    } else if app.mode == AppMode::Cloning {
+        match key.code {
+            KeyCode::Char('x') => app.cancel_clone(),
+            KeyCode::Char('d') => app.clear_finished_clones(),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
+            KeyCode::Char('q') => return Ok(app.request_quit()),
+            _ => {}
+        }
+    } else if app.mode == AppMode::ConfirmCheckout {
+        match key.code {
+            KeyCode::Char('s') | KeyCode::Enter => app.confirm_checkout(true),
+            KeyCode::Char('a') => app.confirm_checkout(false),
+            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Backspace => app.go_back(),
+            _ => {}
+        }
+    } else if app.mode == AppMode::NewBranch {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Char(c) => {
+                app.input.push(c);
+            }
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Esc => {
+                app.go_back();
+            }
+            _ => {}
+        }
+    } else if app.is_searching {
+        match key.code {
+            KeyCode::Enter => {
+                app.is_searching = false;
+            }
+            KeyCode::Char(c) => {
+                app.search_query.push(c);
+                if let AppMode::CategorySelection | AppMode::CloneCategory = app.mode {
+                    app.category_state.select(Some(0));
+                } else {
+                    app.project_state.select(Some(0));
                 }
-            } else if app.mode == AppMode::ChangeBaseDir {
-                match key.code {
-                    KeyCode::Enter => {
-                        app.on_enter()?;
-                    }
-                    KeyCode::Char(c) => {
-                        app.input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        app.input.pop();
-                    }
-                    KeyCode::Esc => {
-                        app.mode = AppMode::MainMenu;
-                    }
-                    _ => {}
+            }
+            KeyCode::Backspace => {
+                app.search_query.pop();
+            }
+            KeyCode::Esc => {
+                app.is_searching = false;
+                app.search_query.clear();
+            }
+            _ => {}
+        }
+    } else if app.mode == AppMode::InputUrl {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Char(c) => {
+                app.input.push(c);
+            }
+            KeyCode::Backspace => {
+                if app.input.is_empty() {
+                    app.mode = AppMode::MainMenu;
+                } else {
+                    app.input.pop();
                 }
-            } else {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char('f') => {
-                        app.toggle_favorite();
-                    }
-                    KeyCode::Char('b') => {
-                        if app.mode == AppMode::ProjectSelection
-                            || app.mode == AppMode::Favorites
-                            || app.mode == AppMode::Recent
-                        {
-                            let query = app.search_query.to_lowercase();
-                            let filtered: Vec<&crate::models::ProjectInfo> = app
-                                .projects
-                                .iter()
-                                .filter(|p| {
-                                    query.is_empty() || p.name.to_lowercase().contains(&query)
-                                })
-                                .collect();
-
-                            if let Some(i) = app.project_state.selected() && i < filtered.len() {
-                                let proj = filtered[i].clone();
-                                app.load_branches(&proj.path);
-                                if !app.branches.is_empty() {
-                                    app.pending_project = Some(proj);
-                                    app.previous_mode = Some(app.mode.clone());
-                                    app.mode = AppMode::BranchSelection;
-                                } else {
-                                    app.status_message = Some((
-                                        "No git branches found!".to_string(),
-                                        std::time::Instant::now(),
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char('t') => {
-                        app.open_terminal()?;
-                    }
-                    KeyCode::Char('r') => {
-                        app.refresh_current_view();
-                    }
-                    KeyCode::Char('/') => {
-                        if app.mode != AppMode::MainMenu && app.mode != AppMode::ThemeSelection
-                        {
-                            app.is_searching = true;
-                        }
-                    }
-                    KeyCode::Char('?') => {
+            }
+            KeyCode::Esc => {
+                app.mode = AppMode::MainMenu;
+            }
+            _ => {}
+        }
+    } else if app.mode == AppMode::AddRoot || app.mode == AppMode::InputManifest {
+        match key.code {
+            KeyCode::Enter => {
+                app.on_enter()?;
+            }
+            KeyCode::Char(c) => {
+                app.input.push(c);
+            }
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Esc => {
+                app.go_back();
+            }
+            _ => {}
+        }
+    } else if app.mode == AppMode::ManageRoots {
+        match key.code {
+            KeyCode::Char('a') => {
+                app.input.clear();
+                app.mode = AppMode::AddRoot;
+            }
+            KeyCode::Char('d') => app.remove_root(),
+            KeyCode::Char('K') => app.move_root(-1),
+            KeyCode::Char('J') => app.move_root(1),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
+            KeyCode::Char('q') => return Ok(app.request_quit()),
+            _ => {}
+        }
+    } else {
+        match key.code {
+            KeyCode::Char('q') => return Ok(app.request_quit()),
+            KeyCode::Char('f') => {
+                app.toggle_favorite();
+            }
+            KeyCode::Char('c') if !app.clones.is_empty() => {
+                app.previous_mode = Some(app.mode.clone());
+                app.mode = AppMode::Cloning;
+            }
+            KeyCode::Char('s') if app.is_project_view() => app.cycle_sort_order(),
+            KeyCode::Char('p') if app.is_project_view() => app.toggle_details(),
+            KeyCode::Char('b') if app.is_project_view() => {
+                if let Some(proj) = app.selected_project() {
+                    app.load_branches(&proj.path)?;
+                    if !app.branches.is_empty() {
+                        app.pending_project = Some(proj);
                         app.previous_mode = Some(app.mode.clone());
-                        app.mode = AppMode::Help;
-                    }
-                    KeyCode::Down | KeyCode::Char('j') => app.next(),
-                    KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                    KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
-                        app.on_enter()?;
+                        app.mode = AppMode::BranchSelection;
+                    } else {
+                        app.status_message = Some((
+                            "No git branches found!".to_string(),
+                            std::time::Instant::now(),
+                        ));
                     }
-                    KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
-                    KeyCode::Esc => {
-                        if !app.search_query.is_empty() {
-                            app.search_query.clear();
-                        } else {
-                            app.go_back();
-                        }
-                    }
-                    _ => {}
                 }
             }
+            KeyCode::Char('t') => {
+                app.open_terminal()?;
+            }
+            KeyCode::Char('r') => {
+                app.refresh_current_view();
+            }
+            KeyCode::Char('/')
+                if app.mode != AppMode::MainMenu && app.mode != AppMode::ThemeSelection =>
+            {
+                app.is_searching = true;
+            }
+            KeyCode::Char('?') => {
+                app.previous_mode = Some(app.mode.clone());
+                app.mode = AppMode::Help;
+            }
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                app.on_enter()?;
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
+            KeyCode::Esc => {
+                if !app.search_query.is_empty() {
+                    app.search_query.clear();
+                } else {
+                    app.go_back();
+                }
+            }
+            _ => {}
         }
     }
+    Ok(false)
 }
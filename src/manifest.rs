@@ -0,0 +1,260 @@
+use crate::clone::{CloneRequest, CloneState};
+use crate::error::{IdeaError, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::VecDeque, fs, path::Path};
+
+/// A list of repositories and the categories they belong in, e.g.
+///
+/// ```toml
+/// [[repo]]
+/// url = "org/api"
+/// category = "dev/work"
+/// branch = "develop"
+/// ```
+///
+/// `clone --manifest` reads it and `manifest export` writes it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    #[serde(default, rename = "repo")]
+    pub repos: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ManifestEntry {
+    /// Anything `giturl::parse` accepts.
+    pub url: String,
+    /// Category label (`root/category`) or category name.
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Folder name, when it differs from the repository name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Manifest> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| IdeaError::Manifest(format!("{}: {}", path.display(), e)))?;
+        Manifest::parse(&text)
+            .map_err(|e| IdeaError::Manifest(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(text: &str) -> std::result::Result<Manifest, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| IdeaError::Manifest(e.to_string()))
+    }
+}
+
+/// What happened to one manifest entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Cloned,
+    /// The folder already existed, so nothing was cloned.
+    Present,
+    Failed(String),
+    Cancelled,
+}
+
+impl Outcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Cloned => "cloned",
+            Outcome::Present => "present",
+            Outcome::Failed(_) => "failed",
+            Outcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// The entries of one manifest clone, tracked until every clone job has finished.
+#[derive(Debug, Default)]
+pub struct Batch {
+    /// Entry label and outcome, in the order entries finished.
+    pub results: Vec<(String, Outcome)>,
+    /// Clone job id and entry label of the clones still running.
+    running: Vec<(usize, String)>,
+    /// Entry label and clone of the entries waiting for a running clone to finish.
+    queued: VecDeque<(String, CloneRequest)>,
+}
+
+impl Batch {
+    pub fn record(&mut self, label: String, outcome: Outcome) {
+        self.results.push((label, outcome));
+    }
+
+    pub fn queue(&mut self, label: String, request: CloneRequest) {
+        self.queued.push_back((label, request));
+    }
+
+    /// The next queued entry to start, as long as fewer than `limit` clones are running.
+    pub fn next_queued(&mut self, limit: usize) -> Option<(String, CloneRequest)> {
+        if self.running.len() < limit {
+            self.queued.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Records every queued entry as cancelled without starting it.
+    pub fn cancel_queued(&mut self) {
+        for (label, _) in self.queued.drain(..) {
+            self.results.push((label, Outcome::Cancelled));
+        }
+    }
+
+    pub fn add_job(&mut self, id: usize, label: String) {
+        self.running.push((id, label));
+    }
+
+    /// Records the final state of clone job `id`. Returns its result, or `None` when the
+    /// job isn't part of this batch or hasn't finished.
+    pub fn finish(&mut self, id: usize, state: &CloneState) -> Option<&(String, Outcome)> {
+        let outcome = match state {
+            CloneState::Running => return None,
+            CloneState::Done => Outcome::Cloned,
+            CloneState::Failed(message) => Outcome::Failed(message.clone()),
+            CloneState::Cancelled => Outcome::Cancelled,
+        };
+        let i = self.running.iter().position(|(job, _)| *job == id)?;
+        let (_, label) = self.running.remove(i);
+        self.results.push((label, outcome));
+        self.results.last()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.running.is_empty() && self.queued.is_empty()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, o)| matches!(o, Outcome::Failed(_)))
+            .count()
+    }
+
+    /// One-line count of the outcomes, e.g. `3 cloned, 1 already present, 1 failed`.
+    pub fn summary(&self) -> String {
+        let count = |f: fn(&Outcome) -> bool| self.results.iter().filter(|(_, o)| f(o)).count();
+        let mut parts = vec![format!("{} cloned", count(|o| *o == Outcome::Cloned))];
+        let present = count(|o| *o == Outcome::Present);
+        if present > 0 {
+            parts.push(format!("{} already present", present));
+        }
+        let cancelled = count(|o| *o == Outcome::Cancelled);
+        if cancelled > 0 {
+            parts.push(format!("{} cancelled", cancelled));
+        }
+        if self.failed() > 0 {
+            parts.push(format!("{} failed", self.failed()));
+        }
+        parts.join(", ")
+    }
+
+    /// The summary followed by each failed entry and its error.
+    pub fn report(&self) -> String {
+        let mut report = self.summary();
+        for (label, outcome) in &self.results {
+            if let Outcome::Failed(message) = outcome {
+                report.push_str(&format!("\n\n{}: {}", label, message));
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+            [[repo]]
+            url = "org/api"
+            category = "dev/work"
+            branch = "develop"
+
+            [[repo]]
+            url = "git@host:org/lib.git"
+            category = "oss"
+            name = "lib-fork"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.repos,
+            vec![
+                ManifestEntry {
+                    url: "org/api".to_string(),
+                    category: "dev/work".to_string(),
+                    branch: Some("develop".to_string()),
+                    name: None,
+                },
+                ManifestEntry {
+                    url: "git@host:org/lib.git".to_string(),
+                    category: "oss".to_string(),
+                    branch: None,
+                    name: Some("lib-fork".to_string()),
+                },
+            ]
+        );
+        assert!(Manifest::parse("[[repo]]\nurl = \"org/api\"").is_err());
+        assert_eq!(Manifest::parse(&manifest.to_toml().unwrap()).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_batch_tracks_jobs_and_summarizes() {
+        let mut batch = Batch::default();
+        batch.record("dev/tool".to_string(), Outcome::Present);
+        batch.add_job(1, "dev/api".to_string());
+        batch.add_job(2, "oss/lib".to_string());
+        assert_eq!(batch.finish(1, &CloneState::Running), None);
+        assert_eq!(batch.finish(7, &CloneState::Done), None);
+        assert_eq!(
+            batch.finish(1, &CloneState::Done),
+            Some(&("dev/api".to_string(), Outcome::Cloned))
+        );
+        assert!(!batch.is_finished());
+        batch.finish(2, &CloneState::Failed("repository not found".to_string()));
+        assert!(batch.is_finished());
+        assert_eq!(batch.summary(), "1 cloned, 1 already present, 1 failed");
+        assert_eq!(
+            batch.report(),
+            "1 cloned, 1 already present, 1 failed\n\noss/lib: repository not found"
+        );
+    }
+
+    #[test]
+    fn test_batch_starts_queued_entries_up_to_the_limit() {
+        let mut batch = Batch::default();
+        for name in ["a", "b", "c", "d"] {
+            batch.queue(name.to_string(), CloneRequest::default());
+        }
+        for id in [1, 2] {
+            let (label, _) = batch.next_queued(2).unwrap();
+            batch.add_job(id, label);
+        }
+        assert!(batch.next_queued(2).is_none());
+        assert_eq!(batch.queued(), 2);
+
+        batch.finish(1, &CloneState::Done);
+        let (label, _) = batch.next_queued(2).unwrap();
+        assert_eq!(label, "c");
+        batch.add_job(3, label);
+        batch.cancel_queued();
+        batch.finish(2, &CloneState::Done);
+        assert!(!batch.is_finished());
+        batch.finish(3, &CloneState::Done);
+        assert!(batch.is_finished());
+        assert_eq!(batch.summary(), "3 cloned, 1 cancelled");
+    }
+}
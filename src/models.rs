@@ -1,6 +1,10 @@
+use crate::error::Result;
 use ratatui::style::Color;
 use serde_derive::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Name of the launcher backed by `Config::idea_path`.
+pub const DEFAULT_IDE: &str = "IntelliJ IDEA";
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum AppMode {
@@ -8,19 +12,40 @@ pub enum AppMode {
     CategorySelection,
     ProjectSelection,
     InputUrl,
+    InputManifest,
+    CloneOptions,
     CloneCategory,
     Favorites,
     Recent,
+    TopProjects,
     ConfirmOpen,
     Help,
     ThemeSelection,
-    ChangeBaseDir,
+    ManageRoots,
+    AddRoot,
     BranchSelection,
+    NewBranch,
+    ConfirmCheckout,
+    Cloning,
+    OpenWith,
+    IdeSetup,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
+    /// Single project folder used before `roots` existed; moved into `roots` by `migrate`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub base_dir: String,
+    #[serde(default)]
+    pub roots: Vec<ProjectRoot>,
+    /// How many folder levels below a root are searched for projects.
+    #[serde(default = "default_scan_depth")]
+    pub scan_depth: usize,
+    /// Folder names (glob patterns) that are never searched.
+    #[serde(default = "default_ignore_globs")]
+    pub ignore_globs: Vec<String>,
+    #[serde(default)]
+    pub project_list: ProjectList,
     pub idea_path: String,
     #[serde(default = "default_terminal_cmd")]
     pub terminal_command: String,
@@ -28,8 +53,136 @@ pub struct Config {
     pub favorites: Vec<String>,
     #[serde(default)]
     pub recent_projects: Vec<String>,
+    /// Open count and last open time per project path, used for frecency ranking.
+    #[serde(default)]
+    pub history: BTreeMap<String, OpenStats>,
     #[serde(default = "default_theme")]
     pub theme: String,
+    #[serde(default)]
+    pub ides: Vec<IdeLauncher>,
+    /// The IDE setup was skipped, so it only opens from the menu from now on.
+    #[serde(default)]
+    pub ide_setup_dismissed: bool,
+    #[serde(default = "default_language_ides")]
+    pub language_ides: BTreeMap<String, String>,
+    #[serde(default)]
+    pub project_ides: BTreeMap<String, String>,
+    #[serde(default = "default_true")]
+    pub import_ide_history: bool,
+    /// Whether the project detail panel is shown next to project lists.
+    #[serde(default)]
+    pub show_details: bool,
+    /// Sort order chosen per project view (`projects`, `favorites`, `recent`, `top`).
+    #[serde(default)]
+    pub sort_orders: BTreeMap<String, SortOrder>,
+}
+
+/// How nested projects are named in the project table.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectList {
+    /// Folder name only.
+    Flat,
+    /// Path relative to the category, so projects sharing a parent sort together.
+    #[default]
+    Grouped,
+}
+
+/// Order of the project table, cycled with `s`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Name,
+    Modified,
+    LastCommit,
+    DirtyFirst,
+    Language,
+    LastOpened,
+    Frecency,
+}
+
+impl SortOrder {
+    const ALL: [SortOrder; 7] = [
+        SortOrder::Name,
+        SortOrder::Modified,
+        SortOrder::LastCommit,
+        SortOrder::DirtyFirst,
+        SortOrder::Language,
+        SortOrder::LastOpened,
+        SortOrder::Frecency,
+    ];
+
+    pub fn next(self) -> SortOrder {
+        let i = Self::ALL.iter().position(|o| *o == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Name => "name",
+            SortOrder::Modified => "modified",
+            SortOrder::LastCommit => "last commit",
+            SortOrder::DirtyFirst => "dirty first",
+            SortOrder::Language => "language",
+            SortOrder::LastOpened => "last opened",
+            SortOrder::Frecency => "frecency",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct OpenStats {
+    pub count: u32,
+    /// Unix seconds.
+    pub last_opened: u64,
+}
+
+impl OpenStats {
+    /// The open count weighted by the age of the last open, so a project opened a lot
+    /// last month ranks below one opened a few times today.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let weight = match now.saturating_sub(self.last_opened) {
+            age if age < 60 * 60 => 4.0,
+            age if age < 24 * 60 * 60 => 2.0,
+            age if age < 7 * 24 * 60 * 60 => 0.5,
+            _ => 0.25,
+        };
+        self.count as f64 * weight
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectRoot {
+    pub name: String,
+    pub path: String,
+}
+
+/// A folder under a project root. `name` is `"."` for the root itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category {
+    pub root: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Category {
+    pub fn is_root(&self) -> bool {
+        self.name == "."
+    }
+
+    pub fn label(&self) -> String {
+        if self.is_root() {
+            self.root.clone()
+        } else {
+            format!("{}/{}", self.root, self.name)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IdeLauncher {
+    pub name: String,
+    pub path: String,
 }
 
 fn default_terminal_cmd() -> String {
@@ -38,19 +191,90 @@ fn default_terminal_cmd() -> String {
 fn default_theme() -> String {
     "Darcula (default)".to_string()
 }
+fn default_scan_depth() -> usize {
+    3
+}
+fn default_ignore_globs() -> Vec<String> {
+    ["node_modules", "target", "vendor", "venv", "build", "dist"]
+        .iter()
+        .map(|g| g.to_string())
+        .collect()
+}
+fn default_true() -> bool {
+    true
+}
+fn default_language_ides() -> BTreeMap<String, String> {
+    [
+        ("Rust", "RustRover"),
+        ("Java", DEFAULT_IDE),
+        ("JS/TS", "WebStorm"),
+        ("Python", "PyCharm"),
+        ("Go", "GoLand"),
+    ]
+    .into_iter()
+    .map(|(lang, ide)| (lang.to_string(), ide.to_string()))
+    .collect()
+}
+
+impl Config {
+    /// Moves a legacy `base_dir` into `roots`.
+    pub fn migrate(&mut self) {
+        if !self.base_dir.is_empty() {
+            if !self.roots.iter().any(|r| r.path == self.base_dir) {
+                let root = ProjectRoot {
+                    name: root_name_for(&self.base_dir, &self.roots),
+                    path: self.base_dir.clone(),
+                };
+                self.roots.insert(0, root);
+            }
+            self.base_dir.clear();
+        }
+    }
+}
+
+/// Derives a root name from the folder name, numbered if it is already taken.
+pub fn root_name_for(path: &str, roots: &[ProjectRoot]) -> String {
+    let base = std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("root")
+        .to_string();
+    let mut name = base.clone();
+    let mut n = 2;
+    while roots.iter().any(|r| r.name == name) {
+        name = format!("{}-{}", base, n);
+        n += 1;
+    }
+    name
+}
 
 impl Default for Config {
     fn default() -> Self {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
-        let base_dir = home.join("dev").to_string_lossy().to_string();
+        let dev = home.join("dev").to_string_lossy().to_string();
 
         Self {
-            base_dir,
+            base_dir: String::new(),
+            roots: vec![ProjectRoot {
+                name: "dev".to_string(),
+                path: dev,
+            }],
+            scan_depth: default_scan_depth(),
+            ignore_globs: default_ignore_globs(),
+            project_list: ProjectList::default(),
             idea_path: "/opt/intellij-idea-ultimate-edition/bin/idea".to_string(),
             terminal_command: default_terminal_cmd(),
             favorites: Vec::new(),
             recent_projects: Vec::new(),
+            history: BTreeMap::new(),
             theme: default_theme(),
+            ides: Vec::new(),
+            ide_setup_dismissed: false,
+            language_ides: default_language_ides(),
+            project_ides: BTreeMap::new(),
+            import_ide_history: true,
+            show_details: false,
+            sort_orders: BTreeMap::new(),
         }
     }
 }
@@ -69,11 +293,120 @@ pub struct Theme {
     pub error: Color,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ProjectInfo {
     pub name: String,
     pub path: PathBuf,
     pub git_branch: Option<String>,
     pub has_changes: bool,
     pub language: Option<String>,
+    pub git_loading: bool,
+    pub frame_title: Option<String>,
+    /// Unix seconds.
+    pub last_opened: Option<u64>,
+    /// Committer date of `HEAD`, Unix seconds.
+    pub last_commit: Option<u64>,
+    /// Modification time of the project folder, Unix seconds.
+    pub modified: Option<u64>,
+    /// Upstream and working tree details behind `git_branch` and `has_changes`.
+    pub git: GitStatus,
+    /// Why the git status could not be read.
+    pub git_error: Option<String>,
+}
+
+/// Branch, upstream and working tree state of a repository.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GitStatus {
+    /// Branch name, or the abbreviated commit when `detached`.
+    pub head: Option<String>,
+    pub detached: bool,
+    pub upstream: Option<String>,
+    /// An upstream is configured but the remote branch no longer exists.
+    pub upstream_gone: bool,
+    pub ahead: u32,
+    pub behind: u32,
+    pub staged: u32,
+    pub unstaged: u32,
+    pub untracked: u32,
+    pub conflicted: u32,
+    pub operation: Option<GitOperation>,
+}
+
+impl GitStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.staged + self.unstaged + self.untracked + self.conflicted > 0
+    }
+}
+
+/// A multi-step git command that was started but not finished.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum GitOperation {
+    Rebase,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl GitOperation {
+    pub fn label(self) -> &'static str {
+        match self {
+            GitOperation::Rebase => "REBASE",
+            GitOperation::Merge => "MERGE",
+            GitOperation::CherryPick => "CHERRY-PICK",
+            GitOperation::Revert => "REVERT",
+            GitOperation::Bisect => "BISECT",
+        }
+    }
+}
+
+/// A branch offered in the branch popup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    /// Short name, e.g. `main` or `origin/main` for a remote-tracking branch.
+    pub name: String,
+    /// Remote of a remote-tracking branch. Remote names may contain `/` themselves.
+    pub remote: Option<String>,
+    /// The branch `HEAD` points at.
+    pub current: bool,
+}
+
+impl Branch {
+    pub fn is_remote(&self) -> bool {
+        self.remote.is_some()
+    }
+
+    /// Name of the local branch created when checking out a remote-tracking branch.
+    pub fn local_name(&self) -> &str {
+        self.remote
+            .as_deref()
+            .and_then(|remote| self.name.strip_prefix(remote)?.strip_prefix('/'))
+            .unwrap_or(&self.name)
+    }
+}
+
+/// A checkout waiting for the user to decide what happens to uncommitted changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingCheckout {
+    pub branch: Branch,
+    /// Continue to the open confirmation after switching.
+    pub open: bool,
+}
+
+/// A project from a JetBrains IDE's own `recentProjects.xml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdeRecent {
+    pub path: PathBuf,
+    pub frame_title: Option<String>,
+    /// Unix seconds.
+    pub timestamp: Option<u64>,
+}
+
+/// Git status for one project, produced by a background worker.
+#[derive(Debug)]
+pub struct GitUpdate {
+    pub generation: u64,
+    pub path: PathBuf,
+    pub status: Result<GitStatus>,
+    pub last_commit: Option<u64>,
 }
@@ -0,0 +1,135 @@
+use crate::models::ProjectInfo;
+
+/// A parsed `/` search: filter tokens plus the remaining text, which is fuzzy-matched on
+/// the project name.
+///
+/// Supported tokens are `lang:<name>`, `branch:<name>`, `path:<part>`, `is:dirty`,
+/// `is:clean`, `is:fav` and `is:git`. Prefixing a token with `-` negates it. Unknown
+/// tokens are kept as plain text.
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    pub text: String,
+    filters: Vec<(Filter, bool)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Filter {
+    Lang(String),
+    Branch(String),
+    Path(String),
+    Dirty,
+    Clean,
+    Fav,
+    Git,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Query {
+        let mut query = Query::default();
+        let mut text = Vec::new();
+        for token in input.split_whitespace() {
+            let (negated, body) = match token.strip_prefix('-') {
+                Some(rest) if rest.contains(':') => (true, rest),
+                _ => (false, token),
+            };
+            match Self::filter(body) {
+                Some(filter) => query.filters.push((filter, !negated)),
+                None => text.push(token),
+            }
+        }
+        query.text = text.join(" ");
+        query
+    }
+
+    fn filter(token: &str) -> Option<Filter> {
+        let (key, value) = token.split_once(':')?;
+        let value = value.to_lowercase();
+        match (key.to_lowercase().as_str(), value.as_str()) {
+            (_, "") => None,
+            ("lang", _) => Some(Filter::Lang(value)),
+            ("branch", _) => Some(Filter::Branch(value)),
+            ("path", _) => Some(Filter::Path(value)),
+            ("is", "dirty") => Some(Filter::Dirty),
+            ("is", "clean") => Some(Filter::Clean),
+            ("is", "fav") => Some(Filter::Fav),
+            ("is", "git") => Some(Filter::Git),
+            _ => None,
+        }
+    }
+
+    /// Whether `project` passes every filter token. The text part is not checked here.
+    pub fn matches(&self, project: &ProjectInfo, favorite: bool) -> bool {
+        let contains = |field: Option<&str>, value: &str| {
+            field.is_some_and(|f| f.to_lowercase().contains(value))
+        };
+        // A repository whose status is still loading counts as git, and as clean until
+        // its status arrives.
+        let git = project.git_loading || project.git_branch.is_some();
+        self.filters.iter().all(|(filter, expected)| {
+            let hit = match filter {
+                Filter::Lang(lang) => contains(project.language.as_deref(), lang),
+                Filter::Branch(branch) => contains(project.git_branch.as_deref(), branch),
+                Filter::Path(part) => contains(project.path.to_str(), part),
+                Filter::Dirty => git && project.has_changes,
+                Filter::Clean => git && !project.has_changes,
+                Filter::Fav => favorite,
+                Filter::Git => git,
+            };
+            hit == *expected
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(language: Option<&str>, branch: Option<&str>, dirty: bool) -> ProjectInfo {
+        ProjectInfo {
+            name: "api".to_string(),
+            path: PathBuf::from("/dev/client/api"),
+            git_branch: branch.map(|b| b.to_string()),
+            has_changes: dirty,
+            language: language.map(|l| l.to_string()),
+            ..ProjectInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_splits_filters_from_text() {
+        let query = Query::parse("lang:Rust svc is:dirty foo:bar is:");
+        assert_eq!(query.text, "svc foo:bar is:");
+        assert_eq!(
+            query.filters,
+            vec![(Filter::Lang("rust".to_string()), true), (Filter::Dirty, true)]
+        );
+        assert_eq!(Query::parse("  ").text, "");
+        assert!(Query::parse("plain").filters.is_empty());
+    }
+
+    #[test]
+    fn test_matches_project_fields() {
+        let rust = project(Some("Rust"), Some("main"), true);
+        assert!(Query::parse("lang:rust branch:ma path:client is:dirty").matches(&rust, false));
+        assert!(!Query::parse("lang:go").matches(&rust, false));
+        assert!(!Query::parse("is:fav").matches(&rust, false));
+        assert!(Query::parse("is:fav").matches(&rust, true));
+        assert!(!Query::parse("-is:dirty").matches(&rust, false));
+
+        let no_git = project(None, None, false);
+        assert!(!Query::parse("is:clean").matches(&no_git, false));
+        assert!(Query::parse("-is:git").matches(&no_git, false));
+    }
+
+    #[test]
+    fn test_matches_repository_still_loading() {
+        let loading = ProjectInfo {
+            git_loading: true,
+            ..project(Some("Rust"), None, false)
+        };
+        assert!(Query::parse("is:git").matches(&loading, false));
+        assert!(Query::parse("is:clean").matches(&loading, false));
+        assert!(!Query::parse("is:dirty").matches(&loading, false));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::app::App;
+use crate::models::Config;
+use glob::Pattern;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Walks folders looking for projects, without descending into a project once found.
+pub struct Scanner {
+    pub max_depth: usize,
+    pub ignore: Vec<Pattern>,
+}
+
+impl Scanner {
+    /// Invalid patterns in `ignore_globs` are skipped.
+    pub fn from_config(config: &Config) -> Scanner {
+        Scanner {
+            max_depth: config.scan_depth.max(1),
+            ignore: config
+                .ignore_globs
+                .iter()
+                .filter_map(|g| Pattern::new(g).ok())
+                .collect(),
+        }
+    }
+
+    /// Project folders below `dir`, at most `max_depth` levels deep, sorted by path.
+    pub fn find_projects(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        let mut visited = HashSet::new();
+        visited.extend(fs::canonicalize(dir));
+        self.walk(dir, self.max_depth, &mut visited, &mut found);
+        found.sort();
+        found
+    }
+
+    /// `visited` holds the canonical paths of folders already walked, so symlinks that loop
+    /// back to a parent are not followed again.
+    fn walk(
+        &self,
+        dir: &Path,
+        depth: usize,
+        visited: &mut HashSet<PathBuf>,
+        found: &mut Vec<PathBuf>,
+    ) {
+        if depth == 0 {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // `fs::metadata` follows symlinks, so linked project folders are found too.
+            if !fs::metadata(&path).map(|m| m.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') || self.ignore.iter().any(|p| p.matches(name)) {
+                continue;
+            }
+            if App::is_project(&path) {
+                found.push(path);
+            } else if fs::canonicalize(&path).is_ok_and(|real| visited.insert(real)) {
+                self.walk(&path, depth - 1, visited, found);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn scanner(max_depth: usize, ignore: &[&str]) -> Scanner {
+        Scanner {
+            max_depth,
+            ignore: ignore.iter().map(|g| Pattern::new(g).unwrap()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_find_projects_stops_at_projects_and_depth() {
+        let dir = tempdir().unwrap();
+        for path in [
+            "client/team/service",
+            "client/team/service/nested",
+            "tool",
+            "a/b/c/too-deep",
+            "web/node_modules/pkg",
+        ] {
+            fs::create_dir_all(dir.path().join(path)).unwrap();
+        }
+        fs::write(dir.path().join("client/team/service/go.mod"), "").unwrap();
+        fs::write(dir.path().join("client/team/service/nested/go.mod"), "").unwrap();
+        fs::write(dir.path().join("tool/Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join("a/b/c/too-deep/Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join("web/node_modules/pkg/package.json"), "").unwrap();
+
+        let found = scanner(3, &["node_modules"]).find_projects(dir.path());
+        assert_eq!(
+            found,
+            vec![
+                dir.path().join("client/team/service"),
+                dir.path().join("tool"),
+            ]
+        );
+        assert_eq!(
+            scanner(1, &[]).find_projects(dir.path()),
+            vec![dir.path().join("tool")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_projects_follows_symlinks_without_looping() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        fs::create_dir_all(outside.path().join("shared")).unwrap();
+        fs::write(outside.path().join("shared/go.mod"), "").unwrap();
+        fs::create_dir_all(dir.path().join("team")).unwrap();
+        symlink(outside.path().join("shared"), dir.path().join("team/shared")).unwrap();
+        // A link back to the root would recurse until the depth limit without the guard.
+        symlink(dir.path(), dir.path().join("team/loop")).unwrap();
+
+        assert_eq!(
+            scanner(10, &[]).find_projects(dir.path()),
+            vec![dir.path().join("team/shared")]
+        );
+    }
+}
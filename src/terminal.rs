@@ -0,0 +1,112 @@
+use crate::error::Result;
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::{
+    backtrace::Backtrace,
+    env, fs, io,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Raw mode, the alternate screen and mouse capture for as long as the guard lives. They
+/// are undone on drop, so an early return or an unwinding panic leaves a usable shell.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> Result<TerminalGuard> {
+        enable_raw_mode()?;
+        // Created before entering the alternate screen so a failure there still restores.
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Undoes everything `TerminalGuard::new` set up. Errors are ignored because this also runs
+/// from the panic hook, and running it twice is harmless.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    );
+}
+
+/// Restores the terminal before the default hook prints a panic, so the message lands on
+/// the normal screen, then saves a crash report next to the config file. A panic on a
+/// background thread only ends that thread while the TUI keeps running, so it is reported
+/// to the crash file alone and the terminal is left as it is.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some("main") {
+            write_crash_report(info);
+            return;
+        }
+        restore();
+        default_hook(info);
+        if let Some(path) = write_crash_report(info) {
+            eprintln!("Crash report written to {}", path.display());
+        }
+    }));
+}
+
+fn write_crash_report(info: &PanicHookInfo) -> Option<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = confy::get_configuration_file_path("idea-tui", None)
+        .ok()?
+        .with_file_name(format!("crash-{}.txt", secs));
+    let report = crash_report(
+        &info.to_string(),
+        secs,
+        &Backtrace::force_capture().to_string(),
+    );
+    fs::create_dir_all(path.parent()?).ok()?;
+    fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+fn crash_report(panic: &str, secs: u64, backtrace: &str) -> String {
+    format!(
+        "idea-tui {} crashed at {} (Unix time) on {}/{}\n\n{}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        secs,
+        env::consts::OS,
+        env::consts::ARCH,
+        panic,
+        backtrace
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_contents() {
+        let report = crash_report("panicked at src/ui.rs:1:1:\nboom", 42, "0: main");
+        assert!(report.starts_with(&format!(
+            "idea-tui {} crashed at 42",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(report.contains("panicked at src/ui.rs:1:1:\nboom"));
+        assert!(report.ends_with("Backtrace:\n0: main\n"));
+    }
+}
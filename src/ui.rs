@@ -1,14 +1,23 @@
 use crate::app::App;
-use crate::models::{AppMode, ProjectInfo, Theme};
+use crate::clone::{CloneField, CloneState};
+use crate::giturl;
+use crate::fuzzy::fuzzy_match;
+use crate::models::{AppMode, GitStatus, Theme};
+use crate::query::Query;
 use crate::theme::get_theme;
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
+    },
 };
 
+/// Width of the progress bars on the clone screen, in cells.
+const PROGRESS_WIDTH: usize = 30;
+
 pub fn ui(f: &mut Frame, app: &mut App) {
     let theme = get_theme(&app.config.theme);
     let chunks = Layout::default()
@@ -24,25 +33,59 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         )
         .split(f.area());
 
-    let title_text = match app.mode {
-        AppMode::MainMenu | AppMode::ConfirmOpen | AppMode::Help | AppMode::ThemeSelection => {
-            " idea-tui ".to_string()
-        }
+    let mut title_text = match app.mode {
+        AppMode::MainMenu
+        | AppMode::ConfirmOpen
+        | AppMode::OpenWith
+        | AppMode::Help
+        | AppMode::ThemeSelection => " idea-tui ".to_string(),
         AppMode::CategorySelection => " Select Category ".to_string(),
         AppMode::ProjectSelection => format!(
             " Projects in {} ",
-            app.selected_category.as_ref().unwrap_or(&"".to_string())
+            app.selected_category
+                .as_ref()
+                .map(|c| c.label())
+                .unwrap_or_default()
         ),
         AppMode::InputUrl => " Clone Repository: Paste URL ".to_string(),
-        AppMode::CloneCategory => " Select Category to Clone into ".to_string(),
+        AppMode::InputManifest => " Clone from Manifest ".to_string(),
+        AppMode::CloneOptions => " Clone Options ".to_string(),
+        AppMode::CloneCategory => {
+            let target = app
+                .category_state
+                .selected()
+                .and_then(|i| app.get_filtered_categories().get(i).cloned())
+                .and_then(|c| app.clone_request(&c).ok());
+            match target {
+                Some(request) => format!(" Clone into {} ", request.target.display()),
+                None => " Select Category to Clone into ".to_string(),
+            }
+        }
         AppMode::Favorites => " Favorite Projects ".to_string(),
         AppMode::Recent => " Recently Opened Projects ".to_string(),
-        AppMode::ChangeBaseDir => " Update Base Directory ".to_string(),
-        AppMode::BranchSelection => format!(
+        AppMode::TopProjects => " Top Projects ".to_string(),
+        AppMode::Cloning => match app.manifest_batch.as_ref().map_or(0, |b| b.queued()) {
+            0 => " Clones ".to_string(),
+            queued => format!(" Clones ({} queued) ", queued),
+        },
+        AppMode::ManageRoots => " Project Roots ".to_string(),
+        AppMode::AddRoot => " Add Project Root ".to_string(),
+        AppMode::IdeSetup => " Set Up IDEs ".to_string(),
+        AppMode::BranchSelection | AppMode::ConfirmCheckout => format!(
             " Select Branch for {} ",
             app.pending_project.as_ref().map(|p| p.name.clone()).unwrap_or_default()
         ),
+        AppMode::NewBranch => format!(
+            " New Branch for {} ",
+            app.pending_project.as_ref().map(|p| p.name.clone()).unwrap_or_default()
+        ),
     };
+    if app.mode != AppMode::Cloning
+        && let Some(job) = app.clones.iter().find(|j| j.is_running())
+    {
+        let percent = job.progress.as_ref().map_or(0, |p| p.percent);
+        title_text.push_str(&format!("· cloning {} {}% (c) ", job.request.name(), percent));
+    }
     f.render_widget(
         Paragraph::new(title_text)
             .style(
@@ -60,7 +103,13 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     );
 
     match app.mode {
-        AppMode::MainMenu | AppMode::ConfirmOpen | AppMode::Help | AppMode::BranchSelection => {
+        AppMode::MainMenu
+        | AppMode::ConfirmOpen
+        | AppMode::OpenWith
+        | AppMode::Help
+        | AppMode::BranchSelection
+        | AppMode::NewBranch
+        | AppMode::ConfirmCheckout => {
             let items: Vec<ListItem> = app
                 .menu_items
                 .iter()
@@ -124,6 +173,8 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         }
         AppMode::CategorySelection | AppMode::CloneCategory => {
             let filtered = app.get_filtered_categories();
+            // Highlight what the categories were ranked by, without project filter tokens.
+            let query = Query::parse(&app.search_query).text;
             let items: Vec<ListItem> = if filtered.is_empty() {
                 vec![
                     ListItem::new("  No results found").style(
@@ -145,7 +196,43 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                         } else {
                             Style::default().fg(theme.text)
                         };
-                        ListItem::new(format!(" {}", c)).style(style)
+                        if !query.is_empty() {
+                            // Ranked results are no longer grouped under their root.
+                            let label = c.label();
+                            let matched = fuzzy_match(&query, &label)
+                                .map(|m| m.indices)
+                                .unwrap_or_default();
+                            ListItem::new(Line::from(highlight_spans(
+                                &label,
+                                &matched,
+                                style,
+                                style
+                                    .fg(theme.highlight)
+                                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                            )))
+                        } else {
+                            let label = if c.is_root() {
+                                Span::styled(format!(" {}", c.root), style.add_modifier(Modifier::BOLD))
+                            } else {
+                                Span::styled(format!("   {}", c.name), style)
+                            };
+                            // A folder of the same name there means the clone would be refused.
+                            let taken = app.mode == AppMode::CloneCategory
+                                && app.clone_request(c).is_ok_and(|r| r.target.exists());
+                            if taken {
+                                ListItem::new(Line::from(vec![
+                                    label,
+                                    Span::styled(
+                                        format!("  ({} exists)", app.clone_options.name.trim()),
+                                        Style::default()
+                                            .fg(theme.error)
+                                            .add_modifier(Modifier::ITALIC),
+                                    ),
+                                ]))
+                            } else {
+                                ListItem::new(Line::from(label))
+                            }
+                        }
                     })
                     .collect()
             };
@@ -163,13 +250,11 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 &mut app.category_state,
             );
         }
-        AppMode::ProjectSelection | AppMode::Favorites | AppMode::Recent => {
-            let query = app.search_query.to_lowercase();
-            let filtered: Vec<&ProjectInfo> = app
-                .projects
-                .iter()
-                .filter(|p| query.is_empty() || p.name.to_lowercase().contains(&query))
-                .collect();
+        AppMode::ProjectSelection
+        | AppMode::Favorites
+        | AppMode::Recent
+        | AppMode::TopProjects => {
+            let filtered = app.filtered_projects();
             let rows: Vec<Row> = if filtered.is_empty() {
                 vec![Row::new(vec![
                     Cell::from("  No results found").style(
@@ -182,7 +267,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 filtered
                     .iter()
                     .enumerate()
-                    .map(|(idx, p)| {
+                    .map(|(idx, (p, matched))| {
                         let is_selected = app.project_state.selected() == Some(idx);
                         let name_style = if is_selected {
                             Style::default()
@@ -192,7 +277,14 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                             Style::default().fg(theme.text)
                         };
 
-                        let mut name_spans = vec![Span::styled(p.name.clone(), name_style)];
+                        let mut name_spans = highlight_spans(
+                            &p.name,
+                            matched,
+                            name_style,
+                            name_style
+                                .fg(theme.highlight)
+                                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                        );
                         if let Some(lang) = &p.language {
                             name_spans.push(Span::styled(
                                 format!(" [{}]", lang),
@@ -201,15 +293,46 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                                     .add_modifier(Modifier::ITALIC),
                             ));
                         }
+                        if let Some(title) = &p.frame_title
+                            && title != &p.name
+                        {
+                            name_spans.push(Span::styled(
+                                format!("  {}", title),
+                                Style::default().fg(theme.no_git),
+                            ));
+                        }
+                        if let Some(secs) = p.last_opened {
+                            name_spans.push(Span::styled(
+                                format!("  {}", format_age(secs)),
+                                Style::default().fg(theme.no_git),
+                            ));
+                        }
 
-                        let git_status = if let Some(branch) = &p.git_branch {
+                        // A cached status is shown until the fresh one arrives.
+                        let git_status = if p.git_loading && p.git_branch.is_none() {
+                            Line::from(vec![Span::styled(
+                                " …",
+                                Style::default().fg(theme.no_git),
+                            )])
+                        } else if p.git_error.is_some() {
+                            Line::from(vec![Span::styled(
+                                " [git error]",
+                                Style::default().fg(theme.error),
+                            )])
+                        } else if let Some(branch) = &p.git_branch {
                             let mut spans =
                                 vec![Span::styled("", Style::default().fg(theme.border))];
                             if p.has_changes {
                                 spans[0] = Span::styled("", Style::default().fg(theme.git_dirty));
                             }
                             spans.push(Span::styled("  ", Style::default().fg(theme.no_git)));
+                            let branch = if p.git.detached {
+                                format!("➦ {}", branch)
+                            } else {
+                                branch.clone()
+                            };
                             spans.push(Span::styled(branch, Style::default().fg(theme.git_branch)));
+                            spans.extend(git_indicators(&p.git, &theme));
                             Line::from(spans)
                         } else {
                             Line::from(vec![Span::styled(
@@ -217,10 +340,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                                 Style::default().fg(theme.no_git),
                             )])
                         };
-                        let is_fav = app
-                            .config
-                            .favorites
-                            .contains(&p.path.to_str().unwrap_or("").to_string());
+                        let is_fav = app.is_favorite(&p.path);
                         let fav_cell = Cell::from(" ").style(Style::default().fg(if is_fav {
                             theme.git_dirty
                         } else {
@@ -234,16 +354,18 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                     })
                     .collect()
             };
-            let title = match app.mode {
-                AppMode::Favorites => " Favorites ",
-                AppMode::Recent => " Recently Opened ",
-                _ => " Projects ",
+            let view = match app.mode {
+                AppMode::Favorites => "Favorites",
+                AppMode::Recent => "Recently Opened",
+                AppMode::TopProjects => "Top Projects",
+                _ => "Projects",
             };
+            let title = format!(" {} · sorted by {} ", view, app.sort_order().label());
             let table = Table::new(
                 rows,
                 [
                     Constraint::Min(30),
-                    Constraint::Length(30),
+                    Constraint::Length(40),
                     Constraint::Length(5),
                 ],
             )
@@ -255,7 +377,22 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             )
             .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight)))
             .row_highlight_style(Style::default().bg(theme.surface));
-            f.render_stateful_widget(table, chunks[1], &mut app.project_state);
+            let (table_area, details_area) = if app.config.show_details {
+                let split = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(chunks[1]);
+                (split[0], Some(split[1]))
+            } else {
+                (chunks[1], None)
+            };
+            // The rows borrow `app`, so render with a copy of the state and store it back.
+            let mut state = app.project_state;
+            f.render_stateful_widget(table, table_area, &mut state);
+            app.project_state = state;
+            if let Some(area) = details_area {
+                render_details(f, app, &theme, area);
+            }
         }
         AppMode::InputUrl => {
             let content = if app.input.is_empty() {
@@ -271,8 +408,19 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                     Style::default().fg(theme.git_dirty),
                 )])
             };
+            // Shows what will actually be cloned while the URL is typed.
+            let resolved = match giturl::parse(&app.input) {
+                _ if app.input.is_empty() => Line::default(),
+                Ok(url) => Line::from(vec![
+                    Span::styled("Clones ", Style::default().fg(theme.no_git)),
+                    Span::styled(url.url, Style::default().fg(theme.git_clean)),
+                    Span::styled(" into ", Style::default().fg(theme.no_git)),
+                    Span::styled(format!("{}/", url.name), Style::default().fg(theme.git_clean)),
+                ]),
+                Err(e) => Line::from(Span::styled(e.to_string(), Style::default().fg(theme.error))),
+            };
             f.render_widget(
-                Paragraph::new(content).block(
+                Paragraph::new(vec![content, Line::default(), resolved]).block(
                     Block::default()
                         .borders(Borders::ALL)
                         .title(" Git Repository URL ")
@@ -281,16 +429,289 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 chunks[1],
             );
         }
-        AppMode::ChangeBaseDir => {
-            let content = Line::from(vec![Span::styled(
-                &app.input,
-                Style::default().fg(theme.highlight),
-            )]);
+        AppMode::IdeSetup => {
+            let block = Block::default()
+                .title(" Installed JetBrains IDEs ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border));
+            if app.ide_items.is_empty() {
+                let text = format!(
+                    "
+No JetBrains IDEs were found in /opt, Toolbox, snap or flatpak locations.
+
+Set idea_path in your config (currently '{}').",
+                    app.config.idea_path
+                );
+                f.render_widget(
+                    Paragraph::new(text)
+                        .block(block)
+                        .alignment(Alignment::Center)
+                        .style(Style::default().fg(theme.error)),
+                    chunks[1],
+                );
+            } else {
+                let items: Vec<ListItem> = app
+                    .ide_items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, ide)| {
+                        let is_selected = app.ide_state.selected() == Some(idx);
+                        let style = if is_selected {
+                            Style::default()
+                                .fg(theme.highlight)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default().fg(theme.text)
+                        };
+                        let checked = app.ide_checked.get(idx).copied().unwrap_or(false);
+                        ListItem::new(Line::from(vec![
+                            Span::styled(
+                                if checked { "[x] " } else { "[ ] " },
+                                Style::default().fg(theme.git_clean),
+                            ),
+                            Span::styled(ide.name.clone(), style),
+                            Span::styled(
+                                format!("  {}", ide.path),
+                                Style::default().fg(theme.no_git),
+                            ),
+                        ]))
+                    })
+                    .collect();
+                f.render_stateful_widget(
+                    List::new(items)
+                        .block(block)
+                        .highlight_style(Style::default())
+                        .highlight_symbol(Span::styled(
+                            "> ",
+                            Style::default().fg(theme.highlight),
+                        )),
+                    chunks[1],
+                    &mut app.ide_state,
+                );
+            }
+        }
+        AppMode::ManageRoots => {
+            let items: Vec<ListItem> = app
+                .config
+                .roots
+                .iter()
+                .enumerate()
+                .map(|(idx, r)| {
+                    let is_selected = app.root_state.selected() == Some(idx);
+                    let style = if is_selected {
+                        Style::default()
+                            .fg(theme.highlight)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.text)
+                    };
+                    let path_style = if std::path::Path::new(&r.path).is_dir() {
+                        Style::default().fg(theme.no_git)
+                    } else {
+                        Style::default()
+                            .fg(theme.error)
+                            .add_modifier(Modifier::ITALIC)
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!(" {}", r.name), style),
+                        Span::styled(format!("  {}", r.path), path_style),
+                    ]))
+                })
+                .collect();
+            f.render_stateful_widget(
+                List::new(items)
+                    .block(
+                        Block::default()
+                            .title(" Roots ")
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(theme.border)),
+                    )
+                    .highlight_style(Style::default())
+                    .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight))),
+                chunks[1],
+                &mut app.root_state,
+            );
+        }
+        AppMode::CloneOptions => {
+            let options = &app.clone_options;
+            let items: Vec<ListItem> = CloneField::ALL
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| {
+                    let is_selected = app.clone_field_state.selected() == Some(idx);
+                    let label_style = if is_selected {
+                        Style::default()
+                            .fg(theme.highlight)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.text)
+                    };
+                    let placeholder = Style::default()
+                        .fg(theme.no_git)
+                        .add_modifier(Modifier::ITALIC);
+                    let checkbox = |checked: bool| {
+                        Span::styled(
+                            if checked { "[x]" } else { "[ ]" },
+                            Style::default().fg(theme.highlight),
+                        )
+                    };
+                    let value = match field {
+                        CloneField::Name => {
+                            Span::styled(options.name.as_str(), Style::default().fg(theme.highlight))
+                        }
+                        CloneField::Branch if options.branch.is_empty() => {
+                            Span::styled("default branch", placeholder)
+                        }
+                        CloneField::Branch => {
+                            Span::styled(options.branch.as_str(), Style::default().fg(theme.highlight))
+                        }
+                        CloneField::Depth if options.depth.is_empty() => {
+                            Span::styled("full history", placeholder)
+                        }
+                        CloneField::Depth => {
+                            Span::styled(options.depth.as_str(), Style::default().fg(theme.highlight))
+                        }
+                        CloneField::Submodules => checkbox(options.submodules),
+                        CloneField::Open => checkbox(options.open),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!(" {:<22}", field.label()), label_style),
+                        value,
+                    ]))
+                })
+                .collect();
+            f.render_stateful_widget(
+                List::new(items)
+                    .block(
+                        Block::default()
+                            .title(format!(
+                                " Clone {} ",
+                                giturl::parse(&app.input).map(|u| u.url).unwrap_or_default()
+                            ))
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(theme.border)),
+                    )
+                    .highlight_style(Style::default())
+                    .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight))),
+                chunks[1],
+                &mut app.clone_field_state,
+            );
+        }
+        AppMode::Cloning => {
+            let items: Vec<ListItem> = app
+                .clones
+                .iter()
+                .enumerate()
+                .map(|(idx, job)| {
+                    let is_selected = app.clone_state.selected() == Some(idx);
+                    let name_style = if is_selected {
+                        Style::default()
+                            .fg(theme.highlight)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.text)
+                    };
+                    let (state, color) = match &job.state {
+                        CloneState::Running => ("cloning", theme.git_dirty),
+                        CloneState::Done => ("done", theme.git_clean),
+                        CloneState::Failed(_) => ("failed", theme.error),
+                        CloneState::Cancelled => ("cancelled", theme.no_git),
+                    };
+                    let percent = match &job.state {
+                        CloneState::Done => 100,
+                        _ => job.progress.as_ref().map_or(0, |p| p.percent),
+                    };
+                    let filled = usize::from(percent) * PROGRESS_WIDTH / 100;
+                    let detail = match &job.state {
+                        CloneState::Failed(message) => {
+                            message.lines().next().unwrap_or_default().to_string()
+                        }
+                        _ => job.progress.as_ref().map(|p| p.label()).unwrap_or_default(),
+                    };
+                    ListItem::new(vec![
+                        Line::from(vec![
+                            Span::styled(format!(" {}", job.request.name()), name_style),
+                            Span::styled(format!("  {}", state), Style::default().fg(color)),
+                            Span::styled(
+                                format!("  {}", job.request.url),
+                                Style::default().fg(theme.no_git),
+                            ),
+                        ]),
+                        Line::from(vec![
+                            Span::styled(
+                                format!(" {}", "█".repeat(filled)),
+                                Style::default().fg(color),
+                            ),
+                            Span::styled(
+                                "░".repeat(PROGRESS_WIDTH - filled),
+                                Style::default().fg(theme.no_git),
+                            ),
+                            Span::styled(
+                                format!(" {:>3}%  {}", percent, detail),
+                                Style::default().fg(theme.text),
+                            ),
+                        ]),
+                        Line::from(""),
+                    ])
+                })
+                .collect();
+            f.render_stateful_widget(
+                List::new(items)
+                    .block(
+                        Block::default()
+                            .title(" Clones ")
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(theme.border)),
+                    )
+                    .highlight_style(Style::default())
+                    .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight))),
+                chunks[1],
+                &mut app.clone_state,
+            );
+        }
+        AppMode::InputManifest => {
+            let content = if app.input.is_empty() {
+                Line::from(vec![Span::styled(
+                    "/path/to/team.toml",
+                    Style::default()
+                        .fg(theme.no_git)
+                        .add_modifier(Modifier::ITALIC),
+                )])
+            } else {
+                Line::from(vec![Span::styled(
+                    &app.input,
+                    Style::default().fg(theme.highlight),
+                )])
+            };
+            f.render_widget(
+                Paragraph::new(content).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" Manifest File ")
+                        .border_style(Style::default().fg(theme.border)),
+                ),
+                chunks[1],
+            );
+        }
+        AppMode::AddRoot => {
+            let content = if app.input.is_empty() {
+                Line::from(vec![Span::styled(
+                    "/path/to/projects  or  name=/path/to/projects",
+                    Style::default()
+                        .fg(theme.no_git)
+                        .add_modifier(Modifier::ITALIC),
+                )])
+            } else {
+                Line::from(vec![Span::styled(
+                    &app.input,
+                    Style::default().fg(theme.highlight),
+                )])
+            };
             f.render_widget(
                 Paragraph::new(content).block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title(" New Base Directory Path ")
+                        .title(" New Root Path ")
                         .border_style(Style::default().fg(theme.border)),
                 ),
                 chunks[1],
@@ -298,11 +719,17 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         }
     }
 
-    if app.mode == AppMode::ConfirmOpen || app.mode == AppMode::Help || app.mode == AppMode::BranchSelection {
+    if app.mode == AppMode::ConfirmOpen
+        || app.mode == AppMode::Help
+        || app.mode == AppMode::BranchSelection
+        || app.mode == AppMode::NewBranch
+        || app.mode == AppMode::ConfirmCheckout
+        || app.mode == AppMode::OpenWith
+    {
         dim_background(f, &theme);
         let area = if app.mode == AppMode::Help {
             centered_rect(70, 70, f.area())
-        } else if app.mode == AppMode::BranchSelection {
+        } else if app.mode == AppMode::BranchSelection || app.mode == AppMode::OpenWith {
             centered_rect(50, 60, f.area())
         } else {
             centered_rect(60, 20, f.area())
@@ -314,12 +741,18 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                     .title(" Confirm ")
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(theme.confirm_border));
+                let ide = app.ide_for(&proj.path, proj.language.as_deref());
+                let question = if proj.path == std::path::Path::new("IDE") {
+                    format!("Launch {}?", ide.name)
+                } else {
+                    format!("Open {} in {}?", proj.name, ide.name)
+                };
                 let text = format!(
                     "
-Open {} in IntelliJ?
+{}
 
-(y)es / (n)o",
-                    proj.name
+(y)es / (n)o / (o)pen with...",
+                    question
                 );
                 f.render_widget(
                     Paragraph::new(text)
@@ -329,13 +762,13 @@ Open {} in IntelliJ?
                     area,
                 );
             }
-        } else if app.mode == AppMode::BranchSelection {
+        } else if app.mode == AppMode::OpenWith {
             let items: Vec<ListItem> = app
-                .branches
+                .ide_items
                 .iter()
                 .enumerate()
-                .map(|(idx, b)| {
-                    let is_selected = app.branch_state.selected() == Some(idx);
+                .map(|(idx, ide)| {
+                    let is_selected = app.ide_state.selected() == Some(idx);
                     let style = if is_selected {
                         Style::default()
                             .fg(theme.highlight)
@@ -343,9 +776,114 @@ Open {} in IntelliJ?
                     } else {
                         Style::default().fg(theme.text)
                     };
-                    ListItem::new(format!(" {}", b)).style(style)
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!(" {}", ide.name), style),
+                        Span::styled(
+                            format!("  {}", ide.path),
+                            Style::default().fg(theme.no_git),
+                        ),
+                    ]))
                 })
                 .collect();
+            f.render_stateful_widget(
+                List::new(items)
+                    .block(
+                        Block::default()
+                            .title(" Open with... ")
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(theme.border)),
+                    )
+                    .highlight_style(Style::default())
+                    .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight))),
+                area,
+                &mut app.ide_state,
+            );
+        } else if app.mode == AppMode::ConfirmCheckout {
+            if let (Some(proj), Some(checkout)) = (&app.pending_project, &app.pending_checkout) {
+                let block = Block::default()
+                    .title(" Uncommitted Changes ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.confirm_border));
+                let text = format!(
+                    "
+{} has uncommitted changes.
+Switch to {}?
+
+(s)tash & switch / switch (a)nyway / (n) cancel",
+                    proj.name,
+                    checkout.branch.local_name()
+                );
+                f.render_widget(
+                    Paragraph::new(text)
+                        .block(block)
+                        .alignment(Alignment::Center)
+                        .style(Style::default().fg(theme.header_text)),
+                    area,
+                );
+            }
+        } else if app.mode == AppMode::NewBranch {
+            let content = if app.input.is_empty() {
+                Line::from(vec![Span::styled(
+                    "feature/my-change",
+                    Style::default()
+                        .fg(theme.no_git)
+                        .add_modifier(Modifier::ITALIC),
+                )])
+            } else {
+                Line::from(vec![Span::styled(
+                    &app.input,
+                    Style::default().fg(theme.highlight),
+                )])
+            };
+            f.render_widget(
+                Paragraph::new(vec![Line::from(""), content])
+                    .alignment(Alignment::Center)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(" New Branch from HEAD ")
+                            .border_style(Style::default().fg(theme.border)),
+                    ),
+                area,
+            );
+        } else if app.mode == AppMode::BranchSelection {
+            // Local and remote-tracking branches are listed under their own headings, so
+            // the selection is mapped from `branches` onto the rendered rows.
+            let mut items: Vec<ListItem> = Vec::new();
+            let mut state = ListState::default();
+            let mut group = None;
+            for (idx, b) in app.branches.iter().enumerate() {
+                if group != Some(b.is_remote()) {
+                    group = Some(b.is_remote());
+                    items.push(ListItem::new(Span::styled(
+                        if b.is_remote() { "Remote" } else { "Local" },
+                        Style::default()
+                            .fg(theme.no_git)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                let is_selected = app.branch_state.selected() == Some(idx);
+                if is_selected {
+                    state.select(Some(items.len()));
+                }
+                let style = if is_selected {
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                let mut spans = vec![if b.is_remote() {
+                    Span::styled(" ", Style::default().fg(theme.no_git))
+                } else {
+                    Span::styled(" ", style)
+                }];
+                spans.push(Span::styled(b.name.as_str(), style));
+                if b.current {
+                    spans.push(Span::styled(" (current)", Style::default().fg(theme.git_clean)));
+                }
+                items.push(ListItem::new(Line::from(spans)));
+            }
             f.render_stateful_widget(
                 List::new(items)
                     .block(
@@ -357,7 +895,7 @@ Open {} in IntelliJ?
                     .highlight_style(Style::default())
                     .highlight_symbol(Span::styled("> ", Style::default().fg(theme.highlight))),
                 area,
-                &mut app.branch_state,
+                &mut state,
             );
         } else {
             let block = Block::default()
@@ -375,7 +913,13 @@ Open {} in IntelliJ?
                     Cell::from("Go Back / Cancel"),
                 ]),
                 Row::new(vec![Cell::from("/"), Cell::from("Search / Filter")]),
+                Row::new(vec![
+                    Cell::from("  lang: branch:"),
+                    Cell::from("path: is:dirty is:fav, -token negates"),
+                ]),
                 Row::new(vec![Cell::from("f"), Cell::from("Toggle Favorite")]),
+                Row::new(vec![Cell::from("s"), Cell::from("Cycle Sort Order")]),
+                Row::new(vec![Cell::from("p"), Cell::from("Toggle Detail Panel")]),
                 Row::new(vec![Cell::from("b"), Cell::from("Switch Branch & Open")]),
                 Row::new(vec![Cell::from("t"), Cell::from("Open Quick Terminal")]),
                 Row::new(vec![Cell::from("r"), Cell::from("Refresh Git Status")]),
@@ -398,19 +942,78 @@ Open {} in IntelliJ?
         }
     }
 
-    let footer_text = if app.is_searching {
+    if app.confirm_quit {
+        dim_background(f, &theme);
+        let area = centered_rect(60, 20, f.area());
+        f.render_widget(Clear, area);
+        let running = app.clones.iter().filter(|j| j.is_running()).count();
+        let text = format!(
+            "
+{} still running. Quitting cancels {} and
+removes the partly cloned {}.
+
+(y)es / (n)o",
+            if running == 1 { "A clone is".to_string() } else { format!("{} clones are", running) },
+            if running == 1 { "it" } else { "them" },
+            if running == 1 { "folder" } else { "folders" },
+        );
+        f.render_widget(
+            Paragraph::new(text)
+                .block(
+                    Block::default()
+                        .title(" Quit ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.confirm_border)),
+                )
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(theme.header_text)),
+            area,
+        );
+    }
+
+    if let Some(error) = &app.error {
+        dim_background(f, &theme);
+        let area = centered_rect(60, 30, f.area());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(error.to_string())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .title(" Error ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.error)),
+                )
+                .style(Style::default().fg(theme.text)),
+            area,
+        );
+    }
+
+    let footer_text = if app.error.is_some() {
+        "Press any key to dismiss".to_string()
+    } else if app.confirm_quit {
+        "y: Cancel Clones & Quit  •  n: Keep Running".to_string()
+    } else if app.is_searching {
         format!("/{} (Press Enter to browse results)", app.search_query)
     } else if let Some((msg, _)) = &app.status_message {
         msg.clone()
     } else {
         match app.mode {
-            AppMode::ConfirmOpen => "y: Yes  •  n: No / Cancel".to_string(),
-            AppMode::BranchSelection => "Enter: Checkout & Open  •  c: Checkout only  •  Backspace: Cancel".to_string(),
+            AppMode::ConfirmOpen => "y: Yes  •  n: No / Cancel  •  o: Open with...".to_string(),
+            AppMode::OpenWith => "Enter: Open & Remember  •  Backspace: Back".to_string(),
+            AppMode::BranchSelection => "Enter: Checkout & Open  •  c: Checkout only  •  n: New branch  •  Backspace: Cancel".to_string(),
+            AppMode::NewBranch => "Enter: Create & Open  •  Esc: Back".to_string(),
+            AppMode::CloneOptions => "↑/↓: Field  •  Type to edit  •  Space: Toggle  •  Enter: Choose Category  •  Esc: Back".to_string(),
+            AppMode::Cloning => "x: Cancel  •  d: Clear Finished  •  Backspace: Back (clones keep running)".to_string(),
+            AppMode::ConfirmCheckout => "s: Stash & Switch  •  a: Switch Anyway  •  n: Cancel".to_string(),
             AppMode::Help => "Press any key to close".to_string(),
             AppMode::ThemeSelection => "Enter: Apply Theme  •  Backspace: Back".to_string(),
-            AppMode::ChangeBaseDir => "Enter: Save Path  •  Backspace: Back".to_string(),
+            AppMode::ManageRoots => "a: Add  •  d: Remove  •  K/J: Move Up/Down  •  Backspace: Back".to_string(),
+            AppMode::AddRoot => "Enter: Save Path  •  Esc: Back".to_string(),
+            AppMode::InputManifest => "Enter: Clone Missing Repositories  •  Esc: Back".to_string(),
+            AppMode::IdeSetup => "Space: Toggle  •  Enter: Save  •  Esc: Skip".to_string(),
             AppMode::MainMenu => "Enter / Right: Select  •  ?: Help  •  q: Quit".to_string(),
-            _ => "/: Search  •  r: Refresh  •  t: Terminal  •  b: Branch  •  f: Favorite  •  Backspace: Back  •  ?: Help".to_string(),
+            _ => "/: Search  •  s: Sort  •  p: Details  •  r: Refresh  •  t: Terminal  •  b: Branch  •  f: Favorite  •  Backspace: Back  •  ?: Help".to_string(),
         }
     };
     f.render_widget(
@@ -429,6 +1032,178 @@ Open {} in IntelliJ?
     );
 }
 
+/// Compact markers after the branch name: operation in progress, ahead/behind or a gone
+/// upstream, then conflicted (`!`), staged (`+`), unstaged (`~`) and untracked (`?`) counts.
+fn git_indicators(status: &GitStatus, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut push = |text: String, color| spans.push(Span::styled(text, Style::default().fg(color)));
+    if let Some(op) = status.operation {
+        push(format!(" {}", op.label()), theme.error);
+    }
+    if status.upstream_gone {
+        push(" gone".to_string(), theme.error);
+    }
+    if status.ahead > 0 {
+        push(format!(" ↑{}", status.ahead), theme.highlight);
+    }
+    if status.behind > 0 {
+        push(format!(" ↓{}", status.behind), theme.git_dirty);
+    }
+    let counts = [
+        ("!", status.conflicted, theme.error),
+        ("+", status.staged, theme.highlight),
+        ("~", status.unstaged, theme.git_dirty),
+        ("?", status.untracked, theme.no_git),
+    ];
+    for (marker, count, color) in counts {
+        if count > 0 {
+            push(format!(" {}{}", marker, count), color);
+        }
+    }
+    spans
+}
+
+fn render_details(f: &mut Frame, app: &App, theme: &Theme, area: Rect) {
+    let label = |text: &'static str| {
+        Span::styled(
+            format!("{:<10}", text),
+            Style::default()
+                .fg(theme.border)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let value = |text: String| Span::styled(text, Style::default().fg(theme.text));
+    let dim = |text: String| Span::styled(text, Style::default().fg(theme.no_git));
+
+    let project = app.selected_project();
+    let details = app
+        .details
+        .as_ref()
+        .filter(|d| project.as_ref().is_some_and(|p| p.path == d.path));
+    let mut lines = Vec::new();
+    match (&project, details) {
+        (None, _) => lines.push(Line::from(dim("No project selected".to_string()))),
+        (Some(p), details) => {
+            lines.push(Line::from(vec![
+                label("Path"),
+                value(p.path.display().to_string()),
+            ]));
+            if let Some(branch) = &p.git_branch {
+                let state = if p.has_changes { " (dirty)" } else { "" };
+                lines.push(Line::from(vec![
+                    label("Branch"),
+                    Span::styled(
+                        format!("{}{}", branch, state),
+                        Style::default().fg(theme.git_branch),
+                    ),
+                ]));
+            }
+            if let Some(error) = &p.git_error {
+                lines.push(Line::from(vec![
+                    label("Git"),
+                    Span::styled(error.clone(), Style::default().fg(theme.error)),
+                ]));
+            }
+            match details {
+                None => lines.push(Line::from(dim("Loading…".to_string()))),
+                Some(d) => {
+                    if let Some(remote) = &d.remote {
+                        lines.push(Line::from(vec![label("Remote"), value(remote.clone())]));
+                    }
+                    if let Some(upstream) = &p.git.upstream {
+                        let state = if p.git.upstream_gone {
+                            " (gone)".to_string()
+                        } else {
+                            format!(" ↑{} ↓{}", p.git.ahead, p.git.behind)
+                        };
+                        lines.push(Line::from(vec![
+                            label("Upstream"),
+                            value(format!("{}{}", upstream, state)),
+                        ]));
+                    }
+                    if let Some(tool) = &d.build_tool {
+                        lines.push(Line::from(vec![label("Build"), value(tool.clone())]));
+                    }
+                    if !d.languages.is_empty() {
+                        lines.push(Line::from(vec![
+                            label("Languages"),
+                            value(d.languages.join(", ")),
+                        ]));
+                    }
+                    if !d.commits.is_empty() {
+                        lines.push(Line::from(""));
+                        lines.push(Line::from(label("Commits")));
+                        for commit in &d.commits {
+                            let (hash, subject) = commit.split_once(' ').unwrap_or((commit, ""));
+                            lines.push(Line::from(vec![
+                                Span::styled(
+                                    format!("{} ", hash),
+                                    Style::default().fg(theme.git_dirty),
+                                ),
+                                value(subject.to_string()),
+                            ]));
+                        }
+                    }
+                    if !d.readme.is_empty() {
+                        lines.push(Line::from(""));
+                        lines.push(Line::from(label("README")));
+                        lines.extend(d.readme.iter().map(|l| Line::from(dim(l.clone()))));
+                    }
+                }
+            }
+        }
+    }
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(" Details ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        ),
+        area,
+    );
+}
+
+/// Splits `text` into spans, styling the chars at `matched` (char indices) differently.
+fn highlight_spans(
+    text: &str,
+    matched: &[usize],
+    base: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_match = matched.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            let style = if run_matched { highlight } else { base };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { highlight } else { base }));
+    }
+    spans
+}
+
+/// Formats a Unix timestamp in seconds as a short relative age like `3d ago`.
+fn format_age(secs: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let age = now.saturating_sub(secs);
+    match age {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", age / 60),
+        3600..86400 => format!("{}h ago", age / 3600),
+        _ => format!("{}d ago", age / 86400),
+    }
+}
+
 fn dim_background(f: &mut Frame, theme: &Theme) {
     let area = f.area();
     let buffer = f.buffer_mut();